@@ -0,0 +1,71 @@
+use macroquad::math::{Rect, Vec2};
+
+/// How a sprite sheet is cut up into frames. Every frame is the same size, and they are numbered from the top left
+/// corner of the sheet, left to right, and then top to bottom
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpriteSheet {
+    pub frame_size: Vec2,
+    pub columns: usize,
+    pub frame_count: usize,
+}
+
+impl SpriteSheet {
+    /// Cuts a texture of the given size up into as many whole frames of `frame_size` as will fit
+    pub fn new(texture_size: Vec2, frame_size: Vec2) -> Self {
+        let columns = (texture_size.x / frame_size.x) as usize;
+        let rows = (texture_size.y / frame_size.y) as usize;
+        SpriteSheet{frame_size, columns, frame_count: columns * rows}
+    }
+
+    /// Where the given frame is on the sheet. Frames past the end of the sheet wrap back around to the start
+    pub fn frame(&self, index: usize) -> Rect {
+        let index = index % self.frame_count.max(1);
+        let column = (index % self.columns.max(1)) as f32;
+        let row = (index / self.columns.max(1)) as f32;
+        Rect::new(column * self.frame_size.x, row * self.frame_size.y, self.frame_size.x, self.frame_size.y)
+    }
+}
+
+/// What an animation does once it gets to its last frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationMode {
+    /// Starts over from the first frame
+    Loop,
+    /// Stays on the last frame
+    Once,
+}
+
+/// A run of frames from a sprite sheet, played back at a fixed rate
+#[derive(Debug, PartialEq)]
+pub struct Animation {
+    pub first_frame: usize,
+    pub frame_count: usize,
+    pub fps: f64,
+    pub mode: AnimationMode,
+}
+
+impl Animation {
+    /// How long, in seconds, it takes to play through every frame once
+    pub fn duration(&self) -> f64 {
+        self.frame_count as f64 / self.fps
+    }
+
+    /// Which frame of the sheet to show once the animation has been playing for `elapsed` seconds
+    pub fn frame_at(&self, elapsed: f64) -> usize {
+        let frames_played = (elapsed.max(0.0) * self.fps) as usize;
+        let frame = match self.mode {
+            AnimationMode::Loop => frames_played % self.frame_count.max(1),
+            AnimationMode::Once => frames_played.min(self.frame_count.saturating_sub(1)),
+        };
+        self.first_frame + frame
+    }
+}
+
+/// A ship coasting along with its engine off
+pub const SHIP_IDLE: Animation = Animation{first_frame: 0, frame_count: 1, fps: 1.0, mode: AnimationMode::Loop};
+
+/// A ship with its engine firing, with the flame flickering between a short and a long burn
+pub const SHIP_THRUST: Animation = Animation{first_frame: 1, frame_count: 2, fps: 15.0, mode: AnimationMode::Loop};
+
+/// A fireball that swells up and burns out, for when a ship is destroyed
+pub const EXPLOSION: Animation = Animation{first_frame: 0, frame_count: 8, fps: 16.0, mode: AnimationMode::Once};
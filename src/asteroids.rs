@@ -0,0 +1,58 @@
+use std::ops::RangeInclusive;
+use crate::components::AsteroidSize;
+
+/// Everything that sets one size of asteroid apart from another
+pub struct AsteroidTier {
+    /// Names (in the texture map) of the textures an asteroid of this size can be drawn with, one is picked at random
+    pub textures: &'static [&'static str],
+    /// Points awarded for destroying an asteroid of this size
+    pub score: i32,
+    /// How much damage an asteroid of this size can take before it is destroyed
+    pub hit_points: i32,
+    /// How many fragments the asteroid breaks into when it is destroyed
+    pub fragment_count: RangeInclusive<u32>,
+    /// The size of the fragments it breaks into, if it breaks up at all
+    pub splits_into: Option<AsteroidSize>,
+    /// How fast (in pixels per frame) an asteroid of this size can move, before any difficulty scaling
+    pub speed: RangeInclusive<f32>,
+    /// Width and height of the asteroid's collision rect, which its outline always fits inside
+    pub collider_size: f32,
+}
+
+/// The definition for each asteroid size, indexed by `AsteroidSize`. Asteroids break down from the largest size into
+/// progressively smaller ones, with the smallest simply being destroyed
+pub const ASTEROID_TIERS: [AsteroidTier; 3] = [
+    AsteroidTier{
+        textures: &["large_asteroid_1", "large_asteroid_2", "large_asteroid_3"],
+        score: 5,
+        hit_points: 3,
+        fragment_count: 2..=3,
+        splits_into: Some(AsteroidSize::Medium),
+        speed: 0.1..=1.0,
+        collider_size: 48.0,
+    },
+    AsteroidTier{
+        textures: &["medium_asteroid"],
+        score: 10,
+        hit_points: 2,
+        fragment_count: 2..=4,
+        splits_into: Some(AsteroidSize::Small),
+        speed: 0.3..=1.5,
+        collider_size: 24.0,
+    },
+    AsteroidTier{
+        textures: &["small_asteroid"],
+        score: 15,
+        hit_points: 1,
+        fragment_count: 0..=0,
+        splits_into: None,
+        speed: 0.5..=2.0,
+        collider_size: 16.0,
+    },
+];
+
+impl AsteroidSize {
+    pub fn tier(&self) -> &'static AsteroidTier {
+        &ASTEROID_TIERS[*self as usize]
+    }
+}
@@ -0,0 +1,125 @@
+use macroquad::camera::Camera2D;
+use macroquad::math::Vec2;
+use rand::Rng;
+use crate::components::CameraKickComponent;
+use crate::render::Canvas;
+
+/// How far, in pixels, the screen can be thrown off center when trauma is at its highest
+const MAX_SHAKE_OFFSET: f32 = 8.0;
+
+/// How much trauma wears off each second
+const TRAUMA_DECAY: f32 = 1.5;
+
+/// How far the camera can zoom out and in, and how much each step of the zoom controls changes it by
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 2.0;
+pub const ZOOM_STEP: f32 = 0.25;
+
+/// How much the camera is kicked around by big events, and for how long they freeze the action
+pub const LARGE_ASTEROID_KICK: CameraKickComponent = CameraKickComponent{trauma: 0.3, hit_stop: 0.04};
+pub const SAUCER_KICK: CameraKickComponent = CameraKickComponent{trauma: 0.4, hit_stop: 0.05};
+pub const PLAYER_DEATH_KICK: CameraKickComponent = CameraKickComponent{trauma: 0.8, hit_stop: 0.15};
+
+/// Feedback effects applied to the whole screen when the world is drawn.
+///
+/// Screen shake is driven by trauma, which big events add to and which wears off over time. The screen shakes by the
+/// square of the trauma, so small knocks are barely noticeable, while big ones really rattle things. Hit-stop freezes
+/// the simulation for a moment, to give the biggest impacts some weight
+pub struct CameraEffects {
+    pub trauma: f32,
+    /// How much longer, in seconds, the simulation stays frozen for
+    pub hit_stop_remaining: f64,
+}
+
+impl Default for CameraEffects {
+    fn default() -> Self {
+        CameraEffects{trauma: 0.0, hit_stop_remaining: 0.0}
+    }
+}
+
+impl CameraEffects {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    /// Freezes the simulation for `duration` seconds from now, unless it is already frozen for longer than that
+    pub fn hit_stop(&mut self, duration: f64) {
+        self.hit_stop_remaining = self.hit_stop_remaining.max(duration);
+    }
+
+    /// Checks whether the simulation is frozen this frame, and then runs the hit-stop down by the `elapsed` seconds
+    /// the frame took. This is counted in real time, as the game clock doesn't move while the simulation is frozen
+    pub fn update_hit_stop(&mut self, elapsed: f64) -> bool {
+        let frozen = self.hit_stop_remaining > 0.0;
+        self.hit_stop_remaining = (self.hit_stop_remaining - elapsed).max(0.0);
+        frozen
+    }
+
+    /// Lets trauma wear off over the `elapsed` seconds since the last frame
+    pub fn update(&mut self, elapsed: f32) {
+        self.trauma = (self.trauma - TRAUMA_DECAY * elapsed).max(0.0);
+    }
+
+    /// How far to move everything in the world this frame, to shake the screen
+    pub fn shake_offset(&self, rng: &mut impl Rng) -> Vec2 {
+        let shake = self.trauma * self.trauma * MAX_SHAKE_OFFSET;
+        Vec2::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0)) * shake
+    }
+}
+
+/// What the camera keeps in view
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Stays fixed on the center of the playfield
+    Fixed,
+    /// Tracks the player's ship around the playfield
+    FollowPlayer,
+}
+
+/// Looks at part of the world, which is drawn onto the canvas. At a zoom of 1, one world unit is one pixel of the canvas
+pub struct GameCamera {
+    pub target: Vec2,
+    pub zoom: f32,
+    pub mode: CameraMode,
+}
+
+impl GameCamera {
+    /// A camera following the player, starting out looking at `target`, at a zoom of 1
+    pub fn new(target: Vec2) -> Self {
+        GameCamera{target, zoom: 1.0, mode: CameraMode::FollowPlayer}
+    }
+
+    /// How much of the world, in world units, is in view at the current zoom
+    pub fn view_size(&self) -> Vec2 {
+        Canvas::size() / self.zoom
+    }
+
+    pub fn zoom_by(&mut self, amount: f32) {
+        self.zoom = (self.zoom + amount).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            CameraMode::Fixed => CameraMode::FollowPlayer,
+            CameraMode::FollowPlayer => CameraMode::Fixed,
+        };
+    }
+
+    /// Points the camera at `focus`, without letting it look past the edges of a playfield of the given size. If the
+    /// playfield is smaller than the view along either axis, the camera is centered on it along that axis instead
+    pub fn look_at(&mut self, focus: Vec2, playfield_size: Vec2) {
+        let half_view = self.view_size() / 2.0;
+        let clamp_axis = |focus: f32, half_view: f32, playfield: f32| {
+            if half_view * 2.0 >= playfield { playfield / 2.0 } else { focus.clamp(half_view, playfield - half_view) }
+        };
+        self.target = Vec2::new(
+            clamp_axis(focus.x, half_view.x, playfield_size.x),
+            clamp_axis(focus.y, half_view.y, playfield_size.y),
+        );
+    }
+
+    /// A macroquad camera for drawing the world onto the canvas
+    pub fn camera_2d(&self, canvas: &Canvas) -> Camera2D {
+        canvas.camera(self.target, self.view_size())
+    }
+}
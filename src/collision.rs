@@ -0,0 +1,116 @@
+use macroquad::math::{Rect, Vec2};
+
+/// Checks whether `rect`, travelling in a straight line from `from` to `to` (both being the top left corner of the
+/// rect) over a single frame, touches `target` at any point along the way.
+///
+/// This is done by growing `target` by the size of the moving rect, and then casting a ray from `from` to `to`
+/// against the grown rect. Doing it this way means a small, fast moving rect can never skip over a target between
+/// two frames, no matter how far it travels in that time.
+///
+/// Only the moving rect is swept, `target` is checked where it ended up at the end of the frame, as if it had been
+/// sitting still the whole time. Anything the target passed through on its way there is missed, so this is only
+/// accurate while the target moves much less in a frame than the rect does, like an asteroid next to a bullet.
+pub fn swept_rect_overlaps(rect: &Rect, from: Vec2, to: Vec2, target: &Rect) -> bool {
+    let expanded = Rect::new(target.x - rect.w, target.y - rect.h, target.w + rect.w, target.h + rect.h);
+    let delta = to - from;
+
+    // Clip the portion of the path that falls inside the expanded rect, one axis at a time. If the entry point is
+    // ever past the exit point, the path misses the rect entirely
+    let mut t_enter: f32 = 0.0;
+    let mut t_exit: f32 = 1.0;
+    let axes = [
+        (from.x, delta.x, expanded.left(), expanded.right()),
+        (from.y, delta.y, expanded.top(), expanded.bottom()),
+    ];
+    for (origin, direction, min, max) in axes {
+        if direction.abs() < f32::EPSILON {
+            // Not moving along this axis, so we have to already be within the rect on it
+            if origin < min || origin > max {
+                return false;
+            }
+        } else {
+            let inverse = 1.0 / direction;
+            let mut t_near = (min - origin) * inverse;
+            let mut t_far = (max - origin) * inverse;
+            if t_near > t_far {
+                std::mem::swap(&mut t_near, &mut t_far);
+            }
+
+            t_enter = t_enter.max(t_near);
+            t_exit = t_exit.min(t_far);
+            if t_enter > t_exit {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// The corners of `rect`, in order around its edge, so it can be checked against polygons
+pub fn rect_outline(rect: &Rect) -> [Vec2; 4] {
+    [
+        Vec2::new(rect.left(), rect.top()),
+        Vec2::new(rect.right(), rect.top()),
+        Vec2::new(rect.right(), rect.bottom()),
+        Vec2::new(rect.left(), rect.bottom()),
+    ]
+}
+
+/// Checks whether two polygons, each given as its points in order around its edge, overlap at all. They do if any of
+/// their edges cross, or if either one lies completely inside the other. Neither polygon has to be convex
+pub fn polygons_overlap(a: &[Vec2], b: &[Vec2]) -> bool {
+    if edges(a).any(|(start, end)| segment_crosses_polygon(start, end, b)) {
+        return true;
+    }
+    a.first().is_some_and(|point| point_in_polygon(*point, b)) || b.first().is_some_and(|point| point_in_polygon(*point, a))
+}
+
+/// Checks whether the line from `from` to `to` touches the polygon anywhere, either by crossing its edge, or by lying
+/// completely inside it
+pub fn segment_hits_polygon(from: Vec2, to: Vec2, polygon: &[Vec2]) -> bool {
+    segment_crosses_polygon(from, to, polygon) || point_in_polygon(from, polygon)
+}
+
+/// Every edge of the polygon, as the pair of points at either end of it, including the one joining the last point
+/// back up with the first
+fn edges(polygon: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    polygon.iter().zip(polygon.iter().cycle().skip(1)).map(|(start, end)| (*start, *end))
+}
+
+fn segment_crosses_polygon(from: Vec2, to: Vec2, polygon: &[Vec2]) -> bool {
+    edges(polygon).any(|(start, end)| segments_intersect(from, to, start, end))
+}
+
+/// Checks whether the segment from `a1` to `a2` crosses, or touches, the segment from `b1` to `b2`
+fn segments_intersect(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> bool {
+    let a = a2 - a1;
+    let b = b2 - b1;
+    let denominator = a.perp_dot(b);
+    if denominator.abs() < f32::EPSILON {
+        // Parallel segments are never counted as crossing, any real overlap between two shapes is still picked up by
+        // the edges next to them
+        return false;
+    }
+
+    // How far along each segment the lines they lie on cross, both have to be within the segments themselves
+    let offset = b1 - a1;
+    let t = offset.perp_dot(b) / denominator;
+    let u = offset.perp_dot(a) / denominator;
+    (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)
+}
+
+/// Checks whether `point` is inside the polygon, by counting how many of its edges a ray heading right from the point
+/// crosses. Inside points cross an odd number of them
+fn point_in_polygon(point: Vec2, polygon: &[Vec2]) -> bool {
+    let mut inside = false;
+    for (start, end) in edges(polygon) {
+        if (start.y > point.y) != (end.y > point.y) {
+            let crossing_x = start.x + (point.y - start.y) / (end.y - start.y) * (end.x - start.x);
+            if point.x < crossing_x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
@@ -1,11 +1,69 @@
 use macroquad::math::{Rect, Vec2};
 use uuid::Uuid;
+use crate::animation::Animation;
+use crate::particles::ParticleEffect;
 
+/// The layers a frame is drawn in, from the bottom up. Everything in one layer is drawn over everything in the layers
+/// below it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderLayer {
+    Background,
+    World,
+    Effects,
+    Hud,
+}
+
+/// Something that is drawn with a texture. `position` is where the sprite's pivot is in the world, and the pivot is
+/// given as a fraction of the sprite's size, so `(0.5, 0.5)` (the default) is its center. The sprite is rotated around
+/// its pivot, and scaled and flipped before it is drawn.
+///
+/// Sprites are drawn in their `layer`, and within a layer, ones with a higher `z_index` are drawn over lower ones.
+/// Textures loaded as sprite sheets only have one `frame` of the sheet drawn at a time, other textures ignore it
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct DrawableComponent {
     pub texture_id: Uuid,
     pub position: Vec2,
-    pub rotation: f32
+    pub rotation: f32,
+    pub pivot: Vec2,
+    pub scale: Vec2,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub layer: RenderLayer,
+    pub z_index: i32,
+    pub frame: usize
+}
+
+impl DrawableComponent {
+    /// A drawable that is centered on `position`, at its normal size, in the bottom of the world layer
+    pub fn new(texture_id: Uuid, position: Vec2, rotation: f32) -> Self {
+        DrawableComponent{
+            texture_id,
+            position,
+            rotation,
+            pivot: Vec2::new(0.5, 0.5),
+            scale: Vec2::ONE,
+            flip_x: false,
+            flip_y: false,
+            layer: RenderLayer::World,
+            z_index: 0,
+            frame: 0,
+        }
+    }
+
+    /// Moves the drawable to the given layer, and depth within it
+    pub fn in_layer(self, layer: RenderLayer, z_index: i32) -> Self {
+        DrawableComponent{layer, z_index, ..self}
+    }
+
+    /// Where the top left corner of the sprite ends up, once it has been scaled and placed around its pivot. The
+    /// sprite size is the size of the whole texture, or of a single frame for sprite sheets
+    pub fn top_left(&self, sprite_size: Vec2) -> Vec2 {
+        self.position - self.pivot * self.size(sprite_size)
+    }
+
+    pub fn size(&self, sprite_size: Vec2) -> Vec2 {
+        sprite_size * self.scale
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -25,6 +83,34 @@ pub struct CollisionComponent {
     pub collided: bool
 }
 
+impl CollisionComponent {
+    /// A collider of the given size, centered on `position`. Colliders are kept centered on their entity's position as
+    /// it moves
+    pub fn centered_on(position: Vec2, width: f32, height: f32) -> Self {
+        CollisionComponent{rect: Rect::new(position.x - width / 2.0, position.y - height / 2.0, width, height), collided: false}
+    }
+}
+
+/// The shape of an entity, as points in order around its edge, relative to the entity's position and before it is
+/// rotated. This is what the entity is drawn as in vector mode, and once its collision rect overlaps something, it is
+/// also what decides whether it really hit it
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutlineComponent {
+    pub points: Vec<Vec2>
+}
+
+impl OutlineComponent {
+    pub fn new(points: &[Vec2]) -> Self {
+        OutlineComponent{points: points.to_vec()}
+    }
+
+    /// Where each point of the outline is in the world, for an entity at `position`, rotated by `rotation`
+    pub fn world_points(&self, position: Vec2, rotation: f32) -> Vec<Vec2> {
+        let rotation = Vec2::from_angle(rotation);
+        self.points.iter().map(|point| position + rotation.rotate(*point)).collect()
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ScoreComponent {
     pub value: i32,
@@ -32,14 +118,279 @@ pub struct ScoreComponent {
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct PlayerComponent {
-    pub fire_rate: f64,
-    pub last_bullet_fired: f64
+    pub hyperspace_cooldown: f64,
+    pub last_hyperspace_jump: f64,
+    pub hyperspace_requested: bool
+}
+
+/// The sizes an asteroid can come in, from largest to smallest. See `asteroids::ASTEROID_TIERS` for how each one
+/// behaves
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AsteroidSize {
+    Large,
+    Medium,
+    Small,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct AsteroidComponent {
-    pub is_large: bool
+    pub size: AsteroidSize
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BulletComponent {
+    pub damage: i32
+}
+
+/// An enemy flying saucer. Saucers strafe across the screen from one side to the other, wandering up and down as they
+/// go, and take shots at the player along the way
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SaucerComponent {
+    pub next_course_change: f64,
+    pub fire_rate: f64,
+    pub last_shot_fired: f64,
+    pub distance_travelled: f32
 }
 
+/// A bullet fired by an enemy. These hurt the player and asteroids, but never award any points
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct BulletComponent;
\ No newline at end of file
+pub struct EnemyBulletComponent {
+    pub damage: i32
+}
+/// Marks an entity as moving fast enough that it could pass straight through a collider between two frames. Entities
+/// with this component are checked along the whole path they travelled during the frame, rather than just where they
+/// ended up. `previous_position` is where the top left corner of its collision rect was at the start of the frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FastMoverComponent {
+    pub previous_position: Vec2
+}
+
+/// Makes an entity immune to collisions until `duration` seconds have passed since `started_at`. Entities with this
+/// component blink while it is active
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InvulnerableComponent {
+    pub started_at: f64,
+    pub duration: f64
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUpKind {
+    SpreadShot,
+    RapidFire,
+    Shield,
+    ExtraLife,
+}
+
+/// A power up floating around, waiting for the player to fly into it and collect it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PowerUpComponent {
+    pub kind: PowerUpKind
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActiveEffect {
+    pub kind: PowerUpKind,
+    pub expires_at: f64
+}
+
+/// The power up effects currently applied to an entity, and when each of them wears off
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ActiveEffectsComponent {
+    pub effects: Vec<ActiveEffect>
+}
+
+impl ActiveEffectsComponent {
+    pub fn is_active(&self, kind: PowerUpKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    /// Applies the effect until `expires_at`. Collecting an effect that is already active just resets its timer
+    pub fn activate(&mut self, kind: PowerUpKind, expires_at: f64) {
+        match self.effects.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => effect.expires_at = expires_at,
+            None => self.effects.push(ActiveEffect{kind, expires_at}),
+        }
+    }
+}
+
+/// The shape of what comes out of a weapon each time it fires
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProjectilePattern {
+    /// A single projectile, straight ahead
+    Single,
+    /// `count` projectiles fanned out around the direction the ship is facing, `angle` radians apart
+    Spread { count: u32, angle: f32 },
+    /// `count` projectiles straight ahead, one after the other, `interval` seconds apart
+    Burst { count: u32, interval: f64 },
+    /// A beam that instantly reaches `length` pixels ahead of the ship, hitting everything along the way
+    Laser { length: f32 },
+    /// A missile that turns up to `turn_rate` radians per frame towards the nearest target
+    HomingMissile { turn_rate: f32 },
+}
+
+/// The weapon an entity fires, and the state of its trigger. See `weapons` for the weapons that are available
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeaponComponent {
+    /// Where this weapon is in `weapons::ARSENAL`
+    pub arsenal_index: usize,
+    pub name: &'static str,
+    pub pattern: ProjectilePattern,
+    pub speed: f32,
+    pub lifetime: f64,
+    pub damage: i32,
+    pub cooldown: f64,
+    pub last_fired: f64,
+    pub trigger_pulled: bool,
+    pub burst_shots_remaining: u32,
+    pub next_burst_shot: f64
+}
+
+/// Steers a projectile towards the nearest target, turning up to `turn_rate` radians each frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HomingComponent {
+    pub turn_rate: f32
+}
+
+/// A laser beam, running from `start` to `end`. Beams hit everything along their length, and aren't stopped by
+/// anything they hit. A beam only deals its damage once, no matter how long it stays on screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LaserBeamComponent {
+    pub start: Vec2,
+    pub end: Vec2,
+    pub damage: i32,
+    pub damage_dealt: bool
+}
+
+/// How much damage an entity can take before it is destroyed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HealthComponent {
+    pub hit_points: i32,
+    pub max_hit_points: i32
+}
+
+/// A shield that soaks up damage before it reaches an entity's health. After going `recharge_delay` seconds without
+/// being hit, the shield starts to recover, one point every `recharge_delay` seconds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShieldComponent {
+    pub points: i32,
+    pub max_points: i32,
+    pub recharge_delay: f64,
+    pub recharge_at: f64
+}
+
+impl ShieldComponent {
+    /// Soaks up as much of the damage as the shield can, and returns whatever is left over
+    pub fn absorb(&mut self, damage: i32, time: f64) -> i32 {
+        let absorbed = damage.min(self.points);
+        self.points -= absorbed;
+        self.recharge_at = time + self.recharge_delay;
+        damage - absorbed
+    }
+}
+
+/// Briefly tints an entity to show that it has just taken damage
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DamageFlashComponent {
+    pub started_at: f64
+}
+
+/// Marks a ship that is materializing after a hyperspace jump. Until `duration` seconds have passed since
+/// `started_at`, the ship can't be controlled, and its shield is down
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HyperspaceComponent {
+    pub started_at: f64,
+    pub duration: f64
+}
+
+/// How a ship handles. Speeds are in pixels (or radians, for turning) per frame, and drag is the fraction of the
+/// ship's speed it keeps each frame. The values come from the ship's definition file, see `ships.rs`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShipPhysicsComponent {
+    pub thrust: f32,
+    pub max_speed: f32,
+    pub angular_acceleration: f32,
+    pub max_angular_velocity: f32,
+    pub drag: f32,
+    pub angular_drag: f32,
+    pub angular_velocity: f32
+}
+
+/// A one off burst of particles, which is emitted the next time the particle systems run. The entity is removed as
+/// soon as that happens
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticleBurstComponent {
+    pub effect: &'static ParticleEffect,
+    pub position: Vec2,
+    pub direction: f32
+}
+
+/// Emits a trail of particles out of the back of an entity, every frame that it is switched on. The particles start
+/// `trail_distance` pixels behind the entity's position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticleEmitterComponent {
+    pub effect: &'static ParticleEffect,
+    pub trail_distance: f32,
+    pub active: bool
+}
+
+/// Plays an animation on an entity's drawable, by stepping through the frames of its sprite sheet
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnimationComponent {
+    pub animation: &'static Animation,
+    pub started_at: f64
+}
+
+impl AnimationComponent {
+    pub fn new(animation: &'static Animation, started_at: f64) -> Self {
+        AnimationComponent{animation, started_at}
+    }
+
+    /// Switches over to a different animation, starting it from the beginning. Asking for the animation that is already
+    /// playing leaves it be, so it can be asked for every frame without getting stuck on its first frame
+    pub fn play(&mut self, animation: &'static Animation, time: f64) {
+        if !std::ptr::eq(self.animation, animation) {
+            *self = AnimationComponent::new(animation, time);
+        }
+    }
+
+    /// Which frame of the sprite sheet should be showing at `time`
+    pub fn frame(&self, time: f64) -> usize {
+        self.animation.frame_at(time - self.started_at)
+    }
+}
+
+/// A one off kick to the camera from something big happening, which adds `trauma` to the screen shake, and freezes the
+/// simulation for `hit_stop` seconds. The entity is removed as soon as the kick has been applied
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraKickComponent {
+    pub trauma: f32,
+    pub hit_stop: f64
+}
+
+/// A floating popup showing how many points a kill was worth, and the combo multiplier they were scored at
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScorePopupComponent {
+    pub points: i32,
+    pub multiplier: i32,
+    pub position: Vec2
+}
+
+/// Fired when the player's score passes one of the score milestones, and they are given an extra life for it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MilestoneReachedComponent {
+    pub score: i32
+}
+
+/// What happens to an entity when it goes off the edge of the playfield. Entities without one wrap around to the other
+/// side
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WrapBehavior {
+    /// Reappears on the opposite edge
+    Wrap,
+    /// Stops dead at the edge
+    Clamp,
+    /// Rebounds off the edge
+    Bounce,
+    /// Is removed from the world as soon as it leaves the playfield
+    DespawnOffscreen,
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use crate::entities::ASTEROID_SPAWN_CLEARANCE;
+use crate::{DifficultyCurve, PlayfieldDimensions, EXTRA_LIFE_SCORE_INTERVAL};
+
+/// Settings for how the game looks and plays, loaded from the settings file. Anything the file leaves out keeps its
+/// default, with the post processing effects switched off
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    /// Makes anything bright glow
+    pub bloom: bool,
+    /// Darkens every other line, like an old CRT monitor
+    pub scanlines: bool,
+    /// Splits the colors apart towards the edges of the screen, like a cheap lens
+    pub chromatic_aberration: bool,
+    /// How the game gets harder from wave to wave
+    pub difficulty: DifficultyCurve,
+    /// How many points apart the extra life milestones are
+    pub extra_life_interval: i32,
+    /// How big the world is
+    pub playfield: PlayfieldDimensions,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings{
+            bloom: false,
+            scanlines: false,
+            chromatic_aberration: false,
+            difficulty: DifficultyCurve::default(),
+            extra_life_interval: EXTRA_LIFE_SCORE_INTERVAL,
+            playfield: PlayfieldDimensions::default(),
+        }
+    }
+}
+
+/// Loads the settings file at `path`
+pub fn load_settings(path: impl AsRef<Path>) -> Result<Settings, String> {
+    let path = path.as_ref();
+    let definition = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    parse_settings(&definition).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Parses a settings file. Effects are switched on and off with `true` or `false`, and everything else is a number
+pub fn parse_settings(definition: &str) -> Result<Settings, String> {
+    let mut values = parse_key_values(definition)?;
+    let defaults = Settings::default();
+    let settings = Settings{
+        bloom: take(&mut values, "bloom", defaults.bloom)?,
+        scanlines: take(&mut values, "scanlines", defaults.scanlines)?,
+        chromatic_aberration: take(&mut values, "chromatic_aberration", defaults.chromatic_aberration)?,
+        difficulty: DifficultyCurve{
+            base_asteroid_count: take(&mut values, "base_asteroid_count", defaults.difficulty.base_asteroid_count)?,
+            asteroids_per_wave: take(&mut values, "asteroids_per_wave", defaults.difficulty.asteroids_per_wave)?,
+            max_asteroid_count: take(&mut values, "max_asteroid_count", defaults.difficulty.max_asteroid_count)?,
+            speed_increase_per_wave: take(&mut values, "speed_increase_per_wave", defaults.difficulty.speed_increase_per_wave)?,
+            max_speed_multiplier: take(&mut values, "max_speed_multiplier", defaults.difficulty.max_speed_multiplier)?,
+        },
+        extra_life_interval: take(&mut values, "extra_life_interval", defaults.extra_life_interval)?,
+        playfield: PlayfieldDimensions{
+            width: take(&mut values, "playfield_width", defaults.playfield.width)?,
+            height: take(&mut values, "playfield_height", defaults.playfield.height)?,
+        },
+    };
+
+    // The milestones have to be some distance apart, or every point scored would be a milestone
+    if settings.extra_life_interval <= 0 {
+        return Err(format!("`extra_life_interval` has to be more than 0, not `{}`", settings.extra_life_interval));
+    }
+
+    // The playfield has to have room for asteroids to be placed well clear of the player, wherever they are
+    let min_playfield_size = 2.0 * ASTEROID_SPAWN_CLEARANCE;
+    for (key, size) in [("playfield_width", settings.playfield.width), ("playfield_height", settings.playfield.height)] {
+        if !size.is_finite() || size < min_playfield_size {
+            return Err(format!("`{}` has to be a number no smaller than {}, not `{}`", key, min_playfield_size, size));
+        }
+    }
+
+    // Anything left over isn't a setting, which is most likely a typo
+    if let Some(key) = values.keys().next() {
+        return Err(format!("unknown key `{}`", key));
+    }
+
+    Ok(settings)
+}
+
+/// Takes the value of `key` out of the parsed file, or hands back `default` if the file leaves it out
+fn take<T: FromStr>(values: &mut HashMap<&str, &str>, key: &str, default: T) -> Result<T, String> {
+    match values.remove(key) {
+        Some(value) => value.parse().map_err(|_| format!("`{}` can't be set to `{}`", key, value)),
+        None => Ok(default),
+    }
+}
+
+/// Parses the format that settings and ship definitions are written in. They are made up of `key = value` lines, blank
+/// lines and lines starting with `#` are ignored. A key can only be given once
+pub fn parse_key_values(definition: &str) -> Result<HashMap<&str, &str>, String> {
+    let mut values = HashMap::new();
+    for (index, line) in definition.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or(format!("line {}: expected `key = value`", index + 1))?;
+        if values.insert(key.trim(), value.trim()).is_some() {
+            return Err(format!("line {}: `{}` is given more than once", index + 1, key.trim()));
+        }
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_settings_rejects_a_playfield_that_isnt_a_number() {
+        assert!(parse_settings("playfield_width = nan").is_err());
+        assert!(parse_settings("playfield_height = NaN").is_err());
+    }
+
+    #[test]
+    fn parse_settings_rejects_an_infinite_playfield() {
+        assert!(parse_settings("playfield_width = inf").is_err());
+        assert!(parse_settings("playfield_height = -inf").is_err());
+    }
+
+    #[test]
+    fn parse_settings_rejects_a_playfield_too_small_to_spawn_asteroids_in() {
+        assert!(parse_settings("playfield_width = 80").is_err());
+        assert!(parse_settings("playfield_height = 199").is_err());
+        assert!(parse_settings("playfield_width = 200\nplayfield_height = 200").is_ok());
+    }
+}
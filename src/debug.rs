@@ -0,0 +1,154 @@
+use std::collections::{BTreeMap, HashMap};
+use legion::{Entity, IntoQuery, Resources, World};
+use legion::storage::ComponentTypeId;
+use legion::world::EntityStore;
+use macroquad::prelude::*;
+use crate::camera::GameCamera;
+use crate::components::{ActiveEffectsComponent, AnimationComponent, AsteroidComponent, BulletComponent, CameraKickComponent, CollisionComponent, DamageFlashComponent, DrawableComponent, EnemyBulletComponent, FastMoverComponent, HealthComponent, HomingComponent, HyperspaceComponent, InvulnerableComponent, LaserBeamComponent, MilestoneReachedComponent, OutlineComponent, ParticleBurstComponent, ParticleEmitterComponent, PlayerComponent, PowerUpComponent, SaucerComponent, ScoreComponent, ScorePopupComponent, ShieldComponent, ShipPhysicsComponent, TimedExistenceComponent, VelocityComponent, WeaponComponent, WrapBehavior};
+use crate::render::{Canvas, CANVAS_HEIGHT};
+
+/// How far ahead the velocity vectors reach, in frames
+const VELOCITY_VECTOR_FRAMES: f32 = 10.0;
+
+const DEBUG_FONT_SIZE: u16 = 14;
+
+/// Where the debug text starts on the canvas, below the lives and any active power ups
+const DEBUG_TEXT_TOP: f32 = 130.0;
+
+/// Where the debug text has to stop on the canvas, so it doesn't run into the equipped weapon
+const DEBUG_TEXT_BOTTOM: f32 = CANVAS_HEIGHT - 30.0;
+
+/// Whether the debug overlay is drawn over the game. It shows every collider and where everything is heading, along
+/// with each entity's ID, how many entities there are of each archetype, and the frame rate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+impl DebugOverlay {
+    /// The overlay starts out switched on if the game was started with `--debug`
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        DebugOverlay{enabled: args.any(|arg| arg == "--debug")}
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+}
+
+/// Short names for every kind of component, to label archetypes with. Legion can only name component types itself in
+/// debug builds, so the overlay keeps its own names, which work in release builds too
+fn component_names() -> HashMap<ComponentTypeId, &'static str> {
+    HashMap::from([
+        (ComponentTypeId::of::<DrawableComponent>(), "Drawable"),
+        (ComponentTypeId::of::<VelocityComponent>(), "Velocity"),
+        (ComponentTypeId::of::<TimedExistenceComponent>(), "TimedExistence"),
+        (ComponentTypeId::of::<CollisionComponent>(), "Collision"),
+        (ComponentTypeId::of::<OutlineComponent>(), "Outline"),
+        (ComponentTypeId::of::<ScoreComponent>(), "Score"),
+        (ComponentTypeId::of::<PlayerComponent>(), "Player"),
+        (ComponentTypeId::of::<AsteroidComponent>(), "Asteroid"),
+        (ComponentTypeId::of::<BulletComponent>(), "Bullet"),
+        (ComponentTypeId::of::<SaucerComponent>(), "Saucer"),
+        (ComponentTypeId::of::<EnemyBulletComponent>(), "EnemyBullet"),
+        (ComponentTypeId::of::<FastMoverComponent>(), "FastMover"),
+        (ComponentTypeId::of::<InvulnerableComponent>(), "Invulnerable"),
+        (ComponentTypeId::of::<PowerUpComponent>(), "PowerUp"),
+        (ComponentTypeId::of::<ActiveEffectsComponent>(), "ActiveEffects"),
+        (ComponentTypeId::of::<WeaponComponent>(), "Weapon"),
+        (ComponentTypeId::of::<HomingComponent>(), "Homing"),
+        (ComponentTypeId::of::<LaserBeamComponent>(), "LaserBeam"),
+        (ComponentTypeId::of::<HealthComponent>(), "Health"),
+        (ComponentTypeId::of::<ShieldComponent>(), "Shield"),
+        (ComponentTypeId::of::<DamageFlashComponent>(), "DamageFlash"),
+        (ComponentTypeId::of::<HyperspaceComponent>(), "Hyperspace"),
+        (ComponentTypeId::of::<ShipPhysicsComponent>(), "ShipPhysics"),
+        (ComponentTypeId::of::<ParticleBurstComponent>(), "ParticleBurst"),
+        (ComponentTypeId::of::<ParticleEmitterComponent>(), "ParticleEmitter"),
+        (ComponentTypeId::of::<AnimationComponent>(), "Animation"),
+        (ComponentTypeId::of::<CameraKickComponent>(), "CameraKick"),
+        (ComponentTypeId::of::<ScorePopupComponent>(), "ScorePopup"),
+        (ComponentTypeId::of::<MilestoneReachedComponent>(), "MilestoneReached"),
+        (ComponentTypeId::of::<WrapBehavior>(), "WrapBehavior"),
+    ])
+}
+
+/// A short name for an archetype, made up of the names of its components, like `Drawable, Velocity, Collision`
+fn archetype_label(component_types: &[ComponentTypeId], component_names: &HashMap<ComponentTypeId, &'static str>) -> String {
+    component_types
+        .iter()
+        .map(|type_id| component_names.get(type_id).copied().unwrap_or("?"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn draw_debug_text(text: &str, position: Vec2, color: Color) {
+    draw_text_ex(
+        text,
+        position.x,
+        position.y,
+        TextParams{
+            font_size: DEBUG_FONT_SIZE,
+            color,
+            ..Default::default()
+        });
+}
+
+/// Draws the debug overlay over the gameplay, if it is switched on. The colliders, velocities and IDs are drawn
+/// through the camera, right on top of the entities they belong to, and the counts and frame rate go in the top left
+/// corner of the canvas
+pub(crate) fn draw_debug_overlay(world: &World, resources: &Resources) {
+    if !resources.get::<DebugOverlay>().is_some_and(|overlay| overlay.enabled) {
+        return;
+    }
+    let (Some(camera), Some(canvas)) = (resources.get::<GameCamera>(), resources.get::<Canvas>()) else {
+        return;
+    };
+
+    set_camera(&camera.camera_2d(&canvas));
+    let mut collider_query = <(&CollisionComponent, Option<&OutlineComponent>, &DrawableComponent)>::query();
+    for (collision, outline, drawable) in collider_query.iter(world) {
+        let rect = collision.rect;
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 1.0, GREEN);
+        if let Some(outline) = outline {
+            let points = outline.world_points(drawable.position, drawable.rotation);
+            for (start, end) in points.iter().zip(points.iter().cycle().skip(1)) {
+                draw_line(start.x, start.y, end.x, end.y, 1.0, YELLOW);
+            }
+        }
+    }
+
+    let mut velocity_query = <(&VelocityComponent, &DrawableComponent)>::query();
+    for (velocity, drawable) in velocity_query.iter(world) {
+        let end = drawable.position + velocity.velocity * VELOCITY_VECTOR_FRAMES;
+        draw_line(drawable.position.x, drawable.position.y, end.x, end.y, 1.0, SKYBLUE);
+    }
+
+    let mut id_query = <(Entity, &DrawableComponent)>::query();
+    for (entity, drawable) in id_query.iter(world) {
+        draw_debug_text(&format!("{:?}", entity), drawable.position + Vec2::new(8.0, -8.0), WHITE);
+    }
+
+    // Entities with exactly the same set of components are stored together in an archetype, so counting them up shows
+    // what is in the world, and how much of it there is
+    let component_names = component_names();
+    let mut archetype_counts = BTreeMap::new();
+    if let Ok(storage) = world.get_component_storage::<Entity>() {
+        for archetype in storage.archetypes().iter().filter(|archetype| !archetype.entities().is_empty()) {
+            let label = archetype_label(archetype.layout().component_types(), &component_names);
+            *archetype_counts.entry(label).or_insert(0) += archetype.entities().len();
+        }
+    }
+
+    set_camera(&canvas.ui_camera());
+    let lines = [format!("FPS: {}", get_fps()), format!("Entities: {}", world.len())]
+        .into_iter()
+        .chain(archetype_counts.iter().map(|(label, count)| format!("{:>4}  {}", count, label)));
+    for (index, line) in lines.enumerate() {
+        let y = DEBUG_TEXT_TOP + index as f32 * DEBUG_FONT_SIZE as f32;
+        if y > DEBUG_TEXT_BOTTOM {
+            break;
+        }
+        draw_debug_text(&line, Vec2::new(5.0, y), YELLOW);
+    }
+}
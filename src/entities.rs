@@ -0,0 +1,256 @@
+use legion::Entity;
+use legion::systems::CommandBuffer;
+use macroquad::math::Vec2;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use uuid::Uuid;
+use crate::components::{ActiveEffectsComponent, AnimationComponent, AsteroidComponent, AsteroidSize, BulletComponent, CollisionComponent, DrawableComponent, EnemyBulletComponent, FastMoverComponent, HealthComponent, HomingComponent, InvulnerableComponent, LaserBeamComponent, MilestoneReachedComponent, OutlineComponent, ParticleBurstComponent, ParticleEmitterComponent, PlayerComponent, PowerUpComponent, PowerUpKind, RenderLayer, SaucerComponent, ScoreComponent, ScorePopupComponent, ShieldComponent, ShipPhysicsComponent, TimedExistenceComponent, VelocityComponent, WeaponComponent, WrapBehavior};
+use crate::animation::{EXPLOSION, SHIP_IDLE};
+use crate::{outlines, weapons, DifficultyCurve, PlayfieldDimensions, TextureMap};
+use crate::outlines::{BULLET_OUTLINE, SAUCER_OUTLINE, SHIP_OUTLINE};
+use crate::particles::{ParticleEffect, ENGINE_EXHAUST};
+use crate::power_ups::POWER_UP_LIFETIME;
+
+/// How long, in seconds, a freshly spawned ship is immune to collisions
+pub const SPAWN_INVULNERABILITY: f64 = 3.0;
+
+/// Where each kind of entity is drawn within the world layer, so bullets always show up over what they're hitting
+const ASTEROID_Z_INDEX: i32 = 0;
+const POWER_UP_Z_INDEX: i32 = 1;
+const SAUCER_Z_INDEX: i32 = 2;
+const SHIP_Z_INDEX: i32 = 3;
+const BULLET_Z_INDEX: i32 = 4;
+
+/// How long, in seconds, the player has to wait between hyperspace jumps
+const HYPERSPACE_COOLDOWN: f64 = 3.0;
+
+/// How long, in seconds, score popups stay on screen
+const SCORE_POPUP_LIFETIME: f64 = 0.8;
+
+/// How long, in seconds, reaching a score milestone is announced for
+const MILESTONE_ANNOUNCEMENT_DURATION: f64 = 2.0;
+
+/// New asteroids will never be placed closer than this to the point they are told to avoid
+pub const ASTEROID_SPAWN_CLEARANCE: f32 = 100.0;
+
+/// How many random spots are tried for each new asteroid before giving up, and putting it in whichever corner of the
+/// playfield is furthest from the point it is avoiding
+const ASTEROID_SPAWN_ATTEMPTS: u32 = 20;
+
+/// Points awarded for shooting down a saucer
+const SAUCER_SCORE: i32 = 50;
+
+/// How long, in seconds, a saucer waits between shots
+const SAUCER_FIRE_RATE: f64 = 1.5;
+
+/// How much damage a saucer can take before it is destroyed
+const SAUCER_HIT_POINTS: i32 = 2;
+
+/// How fast, and for how long, bullets fired by enemies travel, and how much they hurt
+const ENEMY_BULLET_SPEED: f32 = 6.0;
+const ENEMY_BULLET_LIFETIME: f64 = 1.5;
+const ENEMY_BULLET_DAMAGE: i32 = 1;
+
+/// The player's ship has a shield that absorbs this many points of damage. Once the shield is gone, the next hit
+/// destroys the ship. The shield recovers one point every `PLAYER_SHIELD_RECHARGE_DELAY` seconds while the ship isn't
+/// being hit
+pub const PLAYER_SHIELD_POINTS: i32 = 3;
+const PLAYER_SHIELD_RECHARGE_DELAY: f64 = 4.0;
+
+/// Spawns a new player ship at the given position. New ships always start out invulnerable for a short time, so the
+/// player has a chance to get their bearings before anything can hit them.
+///
+/// The ship has more components than legion can push in one go, so unlike the other entities here, it is built up
+/// through a command buffer rather than returned as a tuple
+pub fn spawn_player(cmd: &mut CommandBuffer, position: Vec2, texture_id: Uuid, physics: ShipPhysicsComponent, spawned_at: f64) -> Entity {
+    let entity = cmd.push(
+        (
+            PlayerComponent{hyperspace_cooldown: HYPERSPACE_COOLDOWN, last_hyperspace_jump: 0.0, hyperspace_requested: false},
+            DrawableComponent::new(texture_id, position, 0.0).in_layer(RenderLayer::World, SHIP_Z_INDEX),
+            VelocityComponent{velocity: Vec2::new(0.0, 0.0)},
+            CollisionComponent::centered_on(position, 24., 24.),
+            InvulnerableComponent{started_at: spawned_at, duration: SPAWN_INVULNERABILITY},
+            ActiveEffectsComponent::default(),
+            weapons::BLASTER,
+        )
+    );
+    cmd.add_component(entity, physics);
+    cmd.add_component(entity, AnimationComponent::new(&SHIP_IDLE, spawned_at));
+    cmd.add_component(entity, OutlineComponent::new(&SHIP_OUTLINE));
+    cmd.add_component(entity, ParticleEmitterComponent{effect: &ENGINE_EXHAUST, trail_distance: 12.0, active: false});
+    cmd.add_component(entity, HealthComponent{hit_points: 1, max_hit_points: 1});
+    cmd.add_component(entity, ShieldComponent{points: PLAYER_SHIELD_POINTS, max_points: PLAYER_SHIELD_POINTS, recharge_delay: PLAYER_SHIELD_RECHARGE_DELAY, recharge_at: spawned_at});
+    entity
+}
+
+/// Builds the components for a single asteroid of the given size, heading off in a random direction. Its texture,
+/// score, collider and speed all come from the asteroid's tier, with the speed scaled by `speed_multiplier`, and it
+/// gets a jagged outline all of its own. Returns `None` if none of the tier's textures have been loaded
+pub fn asteroid(rng: &mut impl Rng,
+                size: AsteroidSize,
+                position: Vec2,
+                speed_multiplier: f32,
+                texture_map: &TextureMap) -> Option<(AsteroidComponent, DrawableComponent, VelocityComponent, CollisionComponent, ScoreComponent, HealthComponent, OutlineComponent)> {
+    let tier = size.tier();
+    let texture_name = tier.textures.choose(rng)?;
+    let tex_uuid = texture_map.mapping.get(*texture_name)?;
+    let rotation = rng.gen_range(-10.0..=10.0);
+    let speed = rng.gen_range(tier.speed.clone()) * speed_multiplier;
+
+    Some(
+        (
+            AsteroidComponent{size},
+            DrawableComponent::new(*tex_uuid, position, rotation).in_layer(RenderLayer::World, ASTEROID_Z_INDEX),
+            VelocityComponent{velocity: Vec2::from_angle(rotation) * speed},
+            CollisionComponent::centered_on(position, tier.collider_size, tier.collider_size),
+            ScoreComponent{value: tier.score},
+            HealthComponent{hit_points: tier.hit_points, max_hit_points: tier.hit_points},
+            OutlineComponent{points: outlines::asteroid_outline(rng, tier.collider_size / 2.0)},
+        )
+    )
+}
+
+/// Whichever corner of the playfield is furthest from `point`
+fn furthest_corner(playfield: &PlayfieldDimensions, point: Vec2) -> Vec2 {
+    Vec2::new(
+        if point.x < playfield.width / 2.0 { playfield.width } else { 0.0 },
+        if point.y < playfield.height / 2.0 { playfield.height } else { 0.0 },
+    )
+}
+
+/// Builds the components for all of the large asteroids that make up the given wave, scattered randomly around the
+/// screen. The number of asteroids, and how fast they move, comes from the difficulty curve. If `avoid` is given, no
+/// asteroid will be placed near it (this is usually the player's ship)
+pub fn asteroid_wave(rng: &mut impl Rng,
+                     wave: u32,
+                     curve: &DifficultyCurve,
+                     playfield: &PlayfieldDimensions,
+                     texture_map: &TextureMap,
+                     avoid: Option<Vec2>) -> Vec<(AsteroidComponent, DrawableComponent, VelocityComponent, CollisionComponent, ScoreComponent, HealthComponent, OutlineComponent)> {
+    let speed_multiplier = curve.speed_multiplier(wave);
+    let mut asteroids = Vec::new();
+    for _ in 0..curve.asteroid_count(wave) {
+        let mut pos = Vec2::new(rng.gen_range(0.0..=playfield.width), rng.gen_range(0.0..=playfield.height));
+        if let Some(avoid) = avoid {
+            let mut attempts = 1;
+            while pos.distance(avoid) < ASTEROID_SPAWN_CLEARANCE {
+                if attempts == ASTEROID_SPAWN_ATTEMPTS {
+                    pos = furthest_corner(playfield, avoid);
+                    break;
+                }
+                pos = Vec2::new(rng.gen_range(0.0..=playfield.width), rng.gen_range(0.0..=playfield.height));
+                attempts += 1;
+            }
+        }
+
+        asteroids.extend(asteroid(rng, AsteroidSize::Large, pos, speed_multiplier, texture_map));
+    }
+    asteroids
+}
+
+/// Builds the components for a saucer entering the screen at the given position, and flying straight across it at
+/// the given velocity. It will start wandering up and down on its own once it is in the world
+pub fn saucer(position: Vec2, velocity: Vec2, texture_id: Uuid, spawned_at: f64) -> (SaucerComponent, DrawableComponent, VelocityComponent, CollisionComponent, ScoreComponent, HealthComponent, OutlineComponent) {
+    (
+        SaucerComponent{next_course_change: spawned_at, fire_rate: SAUCER_FIRE_RATE, last_shot_fired: spawned_at, distance_travelled: 0.0},
+        DrawableComponent::new(texture_id, position, 0.0).in_layer(RenderLayer::World, SAUCER_Z_INDEX),
+        VelocityComponent{velocity},
+        CollisionComponent::centered_on(position, 28., 16.),
+        ScoreComponent{value: SAUCER_SCORE},
+        HealthComponent{hit_points: SAUCER_HIT_POINTS, max_hit_points: SAUCER_HIT_POINTS},
+        OutlineComponent::new(&SAUCER_OUTLINE),
+    )
+}
+
+/// Builds the components for a bullet fired by an enemy from the given position, heading in the direction of `angle`.
+/// `wrap` decides what happens to it if it reaches the edge of the screen
+pub fn enemy_bullet(position: Vec2, angle: f32, wrap: WrapBehavior, texture_id: Uuid, fired_at: f64) -> (EnemyBulletComponent, DrawableComponent, VelocityComponent, TimedExistenceComponent, CollisionComponent, FastMoverComponent, WrapBehavior, OutlineComponent) {
+    let collision = CollisionComponent::centered_on(position, 16., 16.);
+    (
+        EnemyBulletComponent{damage: ENEMY_BULLET_DAMAGE},
+        DrawableComponent::new(texture_id, position, 0.0).in_layer(RenderLayer::World, BULLET_Z_INDEX),
+        VelocityComponent{velocity: Vec2::from_angle(angle) * ENEMY_BULLET_SPEED},
+        TimedExistenceComponent{created_at: fired_at, max_lifetime: ENEMY_BULLET_LIFETIME},
+        collision,
+        FastMoverComponent{previous_position: collision.rect.point()},
+        wrap,
+        OutlineComponent::new(&BULLET_OUTLINE),
+    )
+}
+
+/// Builds the components for a power up pickup, slowly drifting away from the given position in a random direction.
+/// It will only stick around for a short while before disappearing
+pub fn power_up(rng: &mut impl Rng, kind: PowerUpKind, position: Vec2, texture_id: Uuid, dropped_at: f64) -> (PowerUpComponent, DrawableComponent, VelocityComponent, CollisionComponent, TimedExistenceComponent) {
+    let direction = rng.gen_range(0.0..std::f32::consts::TAU);
+    (
+        PowerUpComponent{kind},
+        DrawableComponent::new(texture_id, position, 0.0).in_layer(RenderLayer::World, POWER_UP_Z_INDEX),
+        VelocityComponent{velocity: Vec2::from_angle(direction) * rng.gen_range(0.3..=0.8)},
+        CollisionComponent::centered_on(position, 24., 24.),
+        TimedExistenceComponent{created_at: dropped_at, max_lifetime: POWER_UP_LIFETIME},
+    )
+}
+
+/// Builds the components for a bullet fired from the given weapon and position, heading in the direction of `angle`.
+/// `wrap` decides what happens to it if it reaches the edge of the screen
+pub fn bullet(position: Vec2, angle: f32, weapon: &WeaponComponent, wrap: WrapBehavior, texture_id: Uuid, fired_at: f64) -> (BulletComponent, DrawableComponent, VelocityComponent, TimedExistenceComponent, CollisionComponent, FastMoverComponent, WrapBehavior, OutlineComponent) {
+    let collision = CollisionComponent::centered_on(position, 16., 16.);
+    (
+        BulletComponent{damage: weapon.damage},
+        DrawableComponent::new(texture_id, position, angle).in_layer(RenderLayer::World, BULLET_Z_INDEX),
+        VelocityComponent{velocity: Vec2::from_angle(angle) * weapon.speed},
+        TimedExistenceComponent{created_at: fired_at, max_lifetime: weapon.lifetime},
+        collision,
+        FastMoverComponent{previous_position: collision.rect.point()},
+        wrap,
+        OutlineComponent::new(&BULLET_OUTLINE),
+    )
+}
+
+/// Spawns a homing missile, which is just a bullet that steers itself. A bullet already has as many components as
+/// legion can push in one go, so like the player's ship, the missile is built up through a command buffer
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_homing_missile(cmd: &mut CommandBuffer, position: Vec2, angle: f32, turn_rate: f32, weapon: &WeaponComponent, wrap: WrapBehavior, texture_id: Uuid, fired_at: f64) -> Entity {
+    let entity = cmd.push(bullet(position, angle, weapon, wrap, texture_id, fired_at));
+    cmd.add_component(entity, HomingComponent{turn_rate});
+    entity
+}
+
+/// Builds the components for a laser beam fired from the given weapon and position, reaching `length` pixels in the
+/// direction of `angle`
+pub fn laser_beam(position: Vec2, angle: f32, length: f32, weapon: &WeaponComponent, fired_at: f64) -> (LaserBeamComponent, TimedExistenceComponent) {
+    (
+        LaserBeamComponent{start: position, end: position + Vec2::from_angle(angle) * length, damage: weapon.damage, damage_dealt: false},
+        TimedExistenceComponent{created_at: fired_at, max_lifetime: weapon.lifetime},
+    )
+}
+
+/// Builds a popup showing the points scored for a kill at the given position, which disappears after a moment
+pub fn score_popup(points: i32, multiplier: i32, position: Vec2, scored_at: f64) -> (ScorePopupComponent, TimedExistenceComponent) {
+    (
+        ScorePopupComponent{points, multiplier, position},
+        TimedExistenceComponent{created_at: scored_at, max_lifetime: SCORE_POPUP_LIFETIME},
+    )
+}
+
+/// Builds the event for reaching the score milestone at `score`, which sticks around long enough to be announced
+pub fn milestone_reached(score: i32, reached_at: f64) -> (MilestoneReachedComponent, TimedExistenceComponent) {
+    (
+        MilestoneReachedComponent{score},
+        TimedExistenceComponent{created_at: reached_at, max_lifetime: MILESTONE_ANNOUNCEMENT_DURATION},
+    )
+}
+
+/// Builds an explosion that plays through once, over the top of everything in the world, and then disappears
+pub fn explosion(position: Vec2, texture_id: Uuid, started_at: f64) -> (DrawableComponent, AnimationComponent, TimedExistenceComponent) {
+    (
+        DrawableComponent::new(texture_id, position, 0.0).in_layer(RenderLayer::Effects, 0),
+        AnimationComponent::new(&EXPLOSION, started_at),
+        TimedExistenceComponent{created_at: started_at, max_lifetime: EXPLOSION.duration()},
+    )
+}
+
+/// Builds a one off burst of particles from the given effect, heading off around `direction`
+pub fn particle_burst(effect: &'static ParticleEffect, position: Vec2, direction: f32) -> (ParticleBurstComponent,) {
+    (ParticleBurstComponent{effect, position, direction},)
+}
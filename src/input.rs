@@ -1,16 +1,19 @@
-use legion::{IntoQuery, Read, World, Write};
-use macroquad::input::{is_key_down, KeyCode};
-use macroquad::math::{Rect, Vec2};
-use macroquad::time::get_time;
-use crate::{GameState, TextureMap};
-use crate::components::{BulletComponent, CollisionComponent, DrawableComponent, PlayerComponent, TimedExistenceComponent, VelocityComponent};
+use legion::{component, IntoQuery, Read, Resources, World, Write};
+use macroquad::input::{is_key_down, is_key_pressed, KeyCode};
+use macroquad::math::Vec2;
+use crate::{weapons, GameRules, GameState};
+use crate::camera::{GameCamera, ZOOM_STEP};
+use crate::debug::DebugOverlay;
+use crate::render::RenderMode;
+use crate::ships::ShipClasses;
+use crate::components::{DrawableComponent, HyperspaceComponent, ParticleEmitterComponent, PlayerComponent, ShipPhysicsComponent, VelocityComponent, WeaponComponent};
 
 pub trait InputManaged {
     fn map_input(&mut self) -> Vec<Action>;
 }
 
 pub(crate) trait ControlSet {
-    fn execute_action(&mut self, actions: Vec<Action>, world: &mut World, texture_map: &TextureMap) -> Option<GameState>;
+    fn execute_action(&mut self, actions: Vec<Action>, world: &mut World, resources: &mut Resources) -> Option<GameState>;
 }
 
 pub enum Action {
@@ -21,11 +24,27 @@ pub enum Action {
     RotateShipLeft,
     ThrustShip,
     FireBullet,
+    NextWeapon,
+    Hyperspace,
+    NextShip,
+    ToggleClassicMode,
+    ZoomIn,
+    ZoomOut,
+    ToggleCameraFollow,
+    ToggleVectorMode,
+    ToggleDebugOverlay,
     NoOp,
 }
 
 pub struct InputManager;
 
+/// The debug overlay can be switched on and off from any state, so it's ready whenever gameplay is drawn
+fn toggle_debug_overlay(resources: &mut Resources) {
+    if let Some(mut debug_overlay) = resources.get_mut::<DebugOverlay>() {
+        debug_overlay.toggle();
+    }
+}
+
 impl InputManaged for InputManager {
     fn map_input(&mut self) -> Vec<Action> {
         // Keep track of multiple actions, as multiple keys can be pressed at the same time
@@ -52,6 +71,34 @@ impl InputManaged for InputManager {
         if is_key_down(KeyCode::Space) {
             keys.push(KeyCode::Space)
         }
+        // Switching weapons should only happen once per key press, rather than every frame the key is held
+        if is_key_pressed(KeyCode::Tab) {
+            keys.push(KeyCode::Tab)
+        }
+        if is_key_pressed(KeyCode::Down) {
+            keys.push(KeyCode::Down)
+        }
+        if is_key_pressed(KeyCode::S) {
+            keys.push(KeyCode::S)
+        }
+        if is_key_pressed(KeyCode::C) {
+            keys.push(KeyCode::C)
+        }
+        if is_key_pressed(KeyCode::Equal) {
+            keys.push(KeyCode::Equal)
+        }
+        if is_key_pressed(KeyCode::Minus) {
+            keys.push(KeyCode::Minus)
+        }
+        if is_key_pressed(KeyCode::F) {
+            keys.push(KeyCode::F)
+        }
+        if is_key_pressed(KeyCode::V) {
+            keys.push(KeyCode::V)
+        }
+        if is_key_pressed(KeyCode::F3) {
+            keys.push(KeyCode::F3)
+        }
 
         let mut actions: Vec<Action> = Vec::new();
         for key_code in keys.iter(){
@@ -63,6 +110,15 @@ impl InputManaged for InputManager {
                 KeyCode::Left => actions.push(Action::RotateShipLeft),
                 KeyCode::Up => actions.push(Action::ThrustShip),
                 KeyCode::Space => actions.push(Action::FireBullet),
+                KeyCode::Tab => actions.push(Action::NextWeapon),
+                KeyCode::Down => actions.push(Action::Hyperspace),
+                KeyCode::S => actions.push(Action::NextShip),
+                KeyCode::C => actions.push(Action::ToggleClassicMode),
+                KeyCode::Equal => actions.push(Action::ZoomIn),
+                KeyCode::Minus => actions.push(Action::ZoomOut),
+                KeyCode::F => actions.push(Action::ToggleCameraFollow),
+                KeyCode::V => actions.push(Action::ToggleVectorMode),
+                KeyCode::F3 => actions.push(Action::ToggleDebugOverlay),
                 _ => actions.push(Action::NoOp),
             }
         }
@@ -73,55 +129,68 @@ impl InputManaged for InputManager {
 pub struct MainMenuControls;
 
 impl ControlSet for MainMenuControls {
-    fn execute_action(&mut self, actions: Vec<Action>, world: &mut World, texture_map: &TextureMap) -> Option<GameState> {
-        for action in actions.iter() {
-            match action {
-                Action::Confirm => {
-                    return Some(GameState::GamePlay)
+    fn execute_action(&mut self, actions: Vec<Action>, world: &mut World, resources: &mut Resources) -> Option<GameState> {
+        if actions.iter().any(|action| matches!(action, Action::NextShip)) {
+            // The ship for the next game is already waiting in the world, so it just needs to be refitted to match
+            // the newly picked class
+            if let Some(mut ship_classes) = resources.get_mut::<ShipClasses>() {
+                let physics = ship_classes.select_next().physics;
+                let mut query = <(Write<ShipPhysicsComponent>, Read<PlayerComponent>)>::query();
+                for (ship_physics, _) in query.iter_mut(world) {
+                    *ship_physics = physics;
                 }
-                _ =>  return None
             }
         }
-        None
+        if actions.iter().any(|action| matches!(action, Action::ToggleClassicMode)) {
+            if let Some(mut rules) = resources.get_mut::<GameRules>() {
+                rules.classic = !rules.classic;
+            }
+        }
+        if actions.iter().any(|action| matches!(action, Action::ToggleVectorMode)) {
+            if let Some(mut render_mode) = resources.get_mut::<RenderMode>() {
+                render_mode.toggle();
+            }
+        }
+        if actions.iter().any(|action| matches!(action, Action::ToggleDebugOverlay)) {
+            toggle_debug_overlay(resources);
+        }
+        // As with the other menus, only the first action decides whether to move on to another state
+        match actions.first() {
+            Some(Action::Confirm) => Some(GameState::GamePlay),
+            _ => None,
+        }
     }
 }
 
 pub struct PauseControls;
 
 impl ControlSet for PauseControls {
-    fn execute_action(&mut self, actions: Vec<Action>, world: &mut World, texture_map: &TextureMap) -> Option<GameState> {
-        for action in actions.iter() {
-            match action {
-                Action::Confirm => {
-                    return Some(GameState::GamePlay)
-                }
-                _ =>  return None
-            }
+    fn execute_action(&mut self, actions: Vec<Action>, _world: &mut World, resources: &mut Resources) -> Option<GameState> {
+        if actions.iter().any(|action| matches!(action, Action::ToggleDebugOverlay)) {
+            toggle_debug_overlay(resources);
+        }
+        match actions.first() {
+            Some(Action::Confirm) => Some(GameState::GamePlay),
+            _ => None,
         }
-        None
     }
 }
 
 pub struct GameOverControls;
 
 impl ControlSet for GameOverControls {
-    fn execute_action(&mut self, actions: Vec<Action>, world: &mut World, texture_map: &TextureMap) -> Option<GameState> {
-        for action in actions.iter() {
-            match action {
-                Action::Revert => {
-                    return Some(GameState::MainMenu)
-                }
-                _ =>  return None
-            }
+    fn execute_action(&mut self, actions: Vec<Action>, _world: &mut World, _resources: &mut Resources) -> Option<GameState> {
+        match actions.first() {
+            Some(Action::Revert) => Some(GameState::MainMenu),
+            _ => None,
         }
-        None
     }
 }
 
 pub struct GamePlayControls;
 
 impl ControlSet for GamePlayControls {
-    fn execute_action(&mut self, actions: Vec<Action>, world: &mut World, texture_map: &TextureMap) -> Option<GameState>{
+    fn execute_action(&mut self, actions: Vec<Action>, world: &mut World, resources: &mut Resources) -> Option<GameState>{
         let mut return_state: Option<GameState> = None;
         for action in actions.iter(){
             match action {
@@ -132,58 +201,71 @@ impl ControlSet for GamePlayControls {
                     return_state = Some(GameState::Pause);
                 }
                 Action::RotateShipRight => {
-                    let mut query = <(Write<DrawableComponent>, Read<PlayerComponent>)>::query();
-                    for (drawable, _) in query.iter_mut(world) {
-                        drawable.rotation += 0.1;
+                    let mut query = <(Write<ShipPhysicsComponent>, Read<PlayerComponent>)>::query()
+                        .filter(!component::<HyperspaceComponent>());
+                    for (physics, _) in query.iter_mut(world) {
+                        physics.angular_velocity = (physics.angular_velocity + physics.angular_acceleration).min(physics.max_angular_velocity);
                     }
                 },
                 Action::RotateShipLeft => {
-                    let mut query = <(Write<DrawableComponent>, Read<PlayerComponent>)>::query();
-                    for (drawable, _) in query.iter_mut(world) {
-                        drawable.rotation -= 0.1;
+                    let mut query = <(Write<ShipPhysicsComponent>, Read<PlayerComponent>)>::query()
+                        .filter(!component::<HyperspaceComponent>());
+                    for (physics, _) in query.iter_mut(world) {
+                        physics.angular_velocity = (physics.angular_velocity - physics.angular_acceleration).max(-physics.max_angular_velocity);
                     }
                 },
                 Action::ThrustShip => {
-                    let mut query = <(Write<VelocityComponent>, Read<DrawableComponent>, Read<PlayerComponent>)>::query();
-                    for (velocity, drawable, _) in query.iter_mut(world) {
-                        let acceleration = Vec2::from_angle(drawable.rotation) * 0.1;
+                    let mut query = <(Write<VelocityComponent>, Write<ParticleEmitterComponent>, Read<DrawableComponent>, Read<ShipPhysicsComponent>, Read<PlayerComponent>)>::query()
+                        .filter(!component::<HyperspaceComponent>());
+                    for (velocity, exhaust, drawable, physics, _) in query.iter_mut(world) {
+                        let acceleration = Vec2::from_angle(drawable.rotation) * physics.thrust;
                         velocity.velocity += acceleration;
+                        exhaust.active = true;
                     }
                 },
                 Action::FireBullet => {
-                    let frame_t = get_time();
-                    let mut query = <(Read<DrawableComponent>, Read<PlayerComponent>)>::query();
-                    let mut pending_entities = Vec::new();
-                    for (drawable, player) in query.iter_mut(world) {
-                        if frame_t - player.last_bullet_fired  > player.fire_rate {
-                            if let Some(bullet_texture_id) = texture_map.mapping.get("bullet") {
-                                pending_entities.push(
-                                    (
-                                        DrawableComponent{texture_id: *bullet_texture_id, position: drawable.position, rotation: 0.0},
-                                        VelocityComponent{velocity: Vec2::from_angle(drawable.rotation) * 15.},
-                                        TimedExistenceComponent{created_at: frame_t, max_lifetime: 1.0},
-                                        BulletComponent{},
-                                        CollisionComponent{rect: Rect::new(drawable.position[0], drawable.position[1], 16., 16.), collided: false}
-                                    )
-                                );
-                            }
-                        }
+                    // The weapon system takes care of actually firing, and whether the weapon is ready to fire yet
+                    let mut query = <(Write<WeaponComponent>, Read<PlayerComponent>)>::query()
+                        .filter(!component::<HyperspaceComponent>());
+                    for (weapon, _) in query.iter_mut(world) {
+                        weapon.trigger_pulled = true;
                     }
-                    let mut bullet_fired = false;
-                    for pending_entity in pending_entities.iter() {
-                        bullet_fired = true;
-                        // I don't like this...but if we try and create the entity on the world in the loop above, the borrow checker rightfully complains
-                        // TODO: Find a better way to do this
-                        world.push((pending_entity.0.clone(), pending_entity.1.clone(), pending_entity.2.clone(), pending_entity.3.clone(), pending_entity.4.clone()));
+                }
+                Action::NextWeapon => {
+                    let mut query = <(Write<WeaponComponent>, Read<PlayerComponent>)>::query();
+                    for (weapon, _) in query.iter_mut(world) {
+                        weapons::next_weapon(weapon);
                     }
-                    if bullet_fired {
-                        let mut player_query = <Write<PlayerComponent>>::query();
-                        for player in player_query.iter_mut(world) {
-                            player.last_bullet_fired = frame_t;
-                        }
+                }
+                Action::Hyperspace => {
+                    // As with firing, the hyperspace system decides whether the jump is ready to happen
+                    let mut query = <Write<PlayerComponent>>::query()
+                        .filter(!component::<HyperspaceComponent>());
+                    for player in query.iter_mut(world) {
+                        player.hyperspace_requested = true;
+                    }
+                }
+                Action::ZoomIn => {
+                    if let Some(mut camera) = resources.get_mut::<GameCamera>() {
+                        camera.zoom_by(ZOOM_STEP);
+                    }
+                }
+                Action::ZoomOut => {
+                    if let Some(mut camera) = resources.get_mut::<GameCamera>() {
+                        camera.zoom_by(-ZOOM_STEP);
+                    }
+                }
+                Action::ToggleCameraFollow => {
+                    if let Some(mut camera) = resources.get_mut::<GameCamera>() {
+                        camera.toggle_mode();
+                    }
+                }
+                Action::ToggleVectorMode => {
+                    if let Some(mut render_mode) = resources.get_mut::<RenderMode>() {
+                        render_mode.toggle();
                     }
-
                 }
+                Action::ToggleDebugOverlay => toggle_debug_overlay(resources),
                 _ =>  return_state = None
             }
         }
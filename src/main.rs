@@ -1,19 +1,41 @@
 mod input;
 mod components;
 mod systems;
+mod collision;
+mod entities;
+mod asteroids;
+mod power_ups;
+mod weapons;
+mod ships;
+mod particles;
+mod camera;
+mod render;
+mod animation;
+mod outlines;
+mod config;
+mod post_processing;
+mod starfield;
+mod debug;
 
 extern crate rand;
 
 use std::collections::HashMap;
-use legion::{IntoQuery, Resources, Schedule, World};
+use legion::{Resources, Schedule, World};
+use legion::systems::CommandBuffer;
 use macroquad::prelude::*;
-use macroquad::rand::ChooseRandom;
-use rand::Rng;
 use rand::rngs::ThreadRng;
 use uuid::Uuid;
-use crate::components::{AsteroidComponent, CollisionComponent, DrawableComponent, PlayerComponent, ScoreComponent, VelocityComponent};
+use crate::components::WrapBehavior;
+use crate::power_ups::ALL_POWER_UPS;
 use crate::input::{ControlSet, GameOverControls, GamePlayControls, InputManaged, InputManager, MainMenuControls, PauseControls};
-use crate::systems::{apply_velocity_system, destroy_timed_entities_system, handle_bullet_collisions_system, handle_player_collision_system, rotate_asteroids_system};
+use crate::config::Settings;
+use crate::debug::DebugOverlay;
+use crate::camera::{CameraEffects, GameCamera};
+use crate::render::{RenderMode, RenderQueue, Renderer, TextureStore};
+use crate::particles::{ParticlePool, MAX_PARTICLES};
+use crate::ships::ShipClasses;
+use crate::starfield::Starfield;
+use crate::systems::{animate_engines_system, animate_sprites_system, apply_camera_kicks_system, apply_ship_physics_system, apply_velocity_system, award_milestones_system, collect_power_ups_system, decay_combo_system, destroy_timed_entities_system, emit_particles_system, expire_damage_flashes_system, expire_invulnerability_system, expire_power_up_effects_system, finish_hyperspace_system, fire_weapons_system, handle_bullet_collisions_system, handle_player_collision_system, hyperspace_jump_system, recharge_shields_system, respawn_player_system, rotate_asteroids_system, saucer_ai_system, spawn_saucers_system, steer_homing_missiles_system, update_particles_system, wave_progression_system};
 
 #[derive(Clone)]
 enum GameState {
@@ -50,44 +72,222 @@ impl GameStateManager {
         self.active_controls = self.current_state.value()
     }
 
+    #[allow(dead_code)]
     fn revert_state(&mut self){
         self.current_state = self.previous_state.clone()
     }
 }
 
-struct RenderData {
-    position: Vec2,
-    rotation: f32,
-    texture: Uuid,
+/// The size of the window, which is kept up to date as the window is resized
+pub struct ScreenDimensions {
+    width: f32,
+    height: f32,
 }
 
-pub struct ScreenDimensions {
+/// The size of the playfield, in world units. This is fixed, rather than following the size of the window, and can be
+/// changed in the settings file. By default it is twice the size of what the camera shows at once, so the camera
+/// follows the player around it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlayfieldDimensions {
     width: f32,
     height: f32,
 }
 
+impl Default for PlayfieldDimensions {
+    fn default() -> Self {
+        PlayfieldDimensions{width: PLAYFIELD_WIDTH, height: PLAYFIELD_HEIGHT}
+    }
+}
+
+impl PlayfieldDimensions {
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width, self.height)
+    }
+}
+
+/// The game clock, which runs in step with real time, except while a hit-stop has frozen the action. Everything in the
+/// game that happens at a set time goes by this clock, so none of it runs down during a freeze
 pub struct TimeResource {
-    absolute_time: f64
+    absolute_time: f64,
+    /// How long, in seconds, the action has spent frozen this game
+    frozen_time: f64,
 }
 
+/// The player's score, along with their current combo. Kills made in quick succession build up a multiplier that is
+/// applied to the points they are worth, which drops back down a step at a time once the kills stop coming
 pub struct ScoreResource {
-    score: i32
+    score: i32,
+    multiplier: i32,
+    combo_kills: u32,
+    last_kill_at: f64
+}
+
+impl Default for ScoreResource {
+    fn default() -> Self {
+        ScoreResource{score: 0, multiplier: 1, combo_kills: 0, last_kill_at: 0.0}
+    }
+}
+
+impl ScoreResource {
+    /// Scores a kill worth `points` at the current multiplier, which is returned, and then builds up the combo
+    pub fn add_kill(&mut self, points: i32, time: f64) -> i32 {
+        let multiplier = self.multiplier;
+        self.score += points * multiplier;
+        self.last_kill_at = time;
+
+        self.combo_kills += 1;
+        if self.combo_kills >= COMBO_KILLS_PER_STEP && self.multiplier < MAX_COMBO_MULTIPLIER {
+            self.multiplier += 1;
+            self.combo_kills = 0;
+        }
+        multiplier
+    }
+
+    /// Knocks the multiplier down a step if there hasn't been a kill in a while
+    pub fn decay_combo(&mut self, time: f64) {
+        if self.multiplier > 1 && time - self.last_kill_at > COMBO_DECAY_INTERVAL {
+            self.multiplier -= 1;
+            self.combo_kills = 0;
+            self.last_kill_at = time;
+        }
+    }
 }
 
 pub struct GameOverResource {
     game_over: bool
 }
 
+/// The score the last game ended with, for the game over screen
+pub struct FinalScoreResource {
+    score: i32
+}
+
+pub struct LivesResource {
+    lives: i32
+}
+
+pub struct RespawnResource {
+    respawn_pending: bool,
+    /// When the player's ship was last destroyed
+    died_at: f64
+}
+
+pub struct SaucerSpawnResource {
+    next_spawn_at: f64
+}
+
+pub struct WaveResource {
+    wave: u32,
+    interstitial_started_at: Option<f64>
+}
+
+/// Describes how the game gets harder as the player progresses through the waves. Each wave adds more asteroids, and
+/// makes them move faster, up to the given limits. The curve can be tuned in the settings file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DifficultyCurve {
+    /// How many asteroids there are in the first wave
+    pub base_asteroid_count: u32,
+    /// How many more asteroids each wave has than the one before
+    pub asteroids_per_wave: u32,
+    pub max_asteroid_count: u32,
+    /// How much faster asteroids get each wave, as a fraction of their normal speed
+    pub speed_increase_per_wave: f32,
+    pub max_speed_multiplier: f32,
+}
+
+impl DifficultyCurve {
+    pub fn asteroid_count(&self, wave: u32) -> u32 {
+        // The curve can come from the settings file, so don't trust it not to overflow
+        let count = self.asteroids_per_wave.saturating_mul(wave.saturating_sub(1)).saturating_add(self.base_asteroid_count);
+        count.min(self.max_asteroid_count)
+    }
+
+    /// How much faster than normal asteroids move in the given wave
+    pub fn speed_multiplier(&self, wave: u32) -> f32 {
+        let multiplier = 1.0 + self.speed_increase_per_wave * wave.saturating_sub(1) as f32;
+        multiplier.min(self.max_speed_multiplier)
+    }
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        DifficultyCurve{
+            base_asteroid_count: 12,
+            asteroids_per_wave: 2,
+            max_asteroid_count: 30,
+            speed_increase_per_wave: 0.2,
+            max_speed_multiplier: 3.0,
+        }
+    }
+}
+
+/// Rules that can be changed from game to game. Classic mode plays like the original arcade game, where bullets wrap
+/// around the screen, rather than flying off the edge of it
+#[derive(Default)]
+pub struct GameRules {
+    classic: bool,
+}
+
+impl GameRules {
+    /// What happens to bullets (from the player or an enemy) when they reach the edge of the screen
+    pub fn bullet_wrap(&self) -> WrapBehavior {
+        if self.classic { WrapBehavior::Wrap } else { WrapBehavior::DespawnOffscreen }
+    }
+}
+
+/// Scores the player is rewarded with an extra life for reaching. The first is at `interval` points, and then another
+/// every `interval` points after that. The interval can be changed in the settings file
+pub struct ScoreMilestones {
+    interval: i32,
+    next_at: i32,
+}
+
+impl ScoreMilestones {
+    pub fn new(interval: i32) -> Self {
+        ScoreMilestones{interval, next_at: interval}
+    }
+}
+
+impl Default for ScoreMilestones {
+    fn default() -> Self {
+        ScoreMilestones::new(EXTRA_LIFE_SCORE_INTERVAL)
+    }
+}
+
 #[derive(Clone)]
 pub struct TextureMap {
     mapping: HashMap<String, Uuid>
 }
 
+/// The number of ships the player starts each game with
+const STARTING_LIVES: i32 = 3;
+
+/// Size of the playfield, in world units, unless the settings say otherwise
+const PLAYFIELD_WIDTH: f32 = 1280.0;
+const PLAYFIELD_HEIGHT: f32 = 960.0;
+
+/// How many points apart the extra life milestones are, unless the settings say otherwise
+pub const EXTRA_LIFE_SCORE_INTERVAL: i32 = 10000;
+
+/// How many kills in a row it takes to raise the combo multiplier, and how high it can go
+const COMBO_KILLS_PER_STEP: u32 = 3;
+const MAX_COMBO_MULTIPLIER: i32 = 8;
+
+/// How long, in seconds, the player can go without a kill before their combo multiplier drops a step
+const COMBO_DECAY_INTERVAL: f64 = 2.0;
+
+/// How long, in seconds, the "Wave N" message is shown before the next wave of asteroids arrives
+const WAVE_INTERSTITIAL_DURATION: f64 = 3.0;
+
+/// How long, in seconds, into a new game before the first saucer shows up
+const FIRST_SAUCER_DELAY: f64 = 15.0;
+
 fn conf() -> Conf {
     Conf {
         window_title: "MQ GameState".to_string(),
         window_width: 640,
         window_height: 480,
+        window_resizable: true,
         ..Default::default()
     }
 }
@@ -95,48 +295,55 @@ fn conf() -> Conf {
 #[macroquad::main(conf)]
 async fn main() {
 
-    let mut texture_assets = HashMap::new();
+    let mut texture_store = TextureStore::default();
     let mut texture_map: TextureMap  = TextureMap{mapping: HashMap::new()};
     let mut rng = rand::thread_rng();
 
     // Load our textures
-    let ship_texture: Texture2D = load_texture("resources/ship.png").await.unwrap();
-    let bullet_texture: Texture2D = load_texture("resources/bullet.png").await.unwrap();
-    let large_asteroid_texture_1: Texture2D = load_texture("resources/asteroid_2.png").await.unwrap();
-    let large_asteroid_texture_2: Texture2D = load_texture("resources/asteroid_3.png").await.unwrap();
-    let large_asteroid_texture_3: Texture2D = load_texture("resources/asteroid_4.png").await.unwrap();
-    let small_asteroid_texture: Texture2D = load_texture("resources/small_asteroid.png").await.unwrap();
-
-    let ship_texture_id = Uuid::new_v4();
-    let bullet_texture_id = Uuid::new_v4();
-    let large_asteroid1_texture_id = Uuid::new_v4();
-    let large_asteroid2_texture_id = Uuid::new_v4();
-    let large_asteroid3_texture_id = Uuid::new_v4();
-    let small_asteroid_texture_id = Uuid::new_v4();
-
-    texture_map.mapping.insert("ship".to_string(), ship_texture_id);
-    texture_map.mapping.insert("bullet".to_string(), bullet_texture_id);
-    texture_map.mapping.insert("large_asteroid_1".to_string(), large_asteroid1_texture_id);
-    texture_map.mapping.insert("large_asteroid_2".to_string(), large_asteroid2_texture_id);
-    texture_map.mapping.insert("large_asteroid_3".to_string(), large_asteroid3_texture_id);
-    texture_map.mapping.insert("small_asteroid".to_string(), small_asteroid_texture_id);
-
-    texture_assets.insert(ship_texture_id, ship_texture);
-    texture_assets.insert(bullet_texture_id, bullet_texture);
-    texture_assets.insert(large_asteroid1_texture_id, large_asteroid_texture_1);
-    texture_assets.insert(large_asteroid2_texture_id, large_asteroid_texture_2);
-    texture_assets.insert(large_asteroid3_texture_id, large_asteroid_texture_3);
-    texture_assets.insert(small_asteroid_texture_id, small_asteroid_texture);
-
-    // Add the large asteroid textures to a vector, so we can randomly choose one each time we
-    // instantiate a new large asteroid
-    let mut large_asteroid_textures: Vec<Uuid> = Vec::new();
-    large_asteroid_textures.push(large_asteroid1_texture_id);
-    large_asteroid_textures.push(large_asteroid2_texture_id);
-    large_asteroid_textures.push(large_asteroid3_texture_id);
+    let textures = [
+        ("ship", "resources/ship.png"),
+        ("bullet", "resources/bullet.png"),
+        ("large_asteroid_1", "resources/asteroid_2.png"),
+        ("large_asteroid_2", "resources/asteroid_3.png"),
+        ("large_asteroid_3", "resources/asteroid_4.png"),
+        ("saucer", "resources/saucer.png"),
+        ("medium_asteroid", "resources/asteroid_1.png"),
+        ("small_asteroid", "resources/small_asteroid.png"),
+    ];
+    for (name, path) in textures {
+        texture_store.load(&mut texture_map, name, path).await;
+    }
+
+    // Anything animated is loaded as a sprite sheet, along with the size of each frame on it
+    let sprite_sheets = [
+        ("ship_sheet", "resources/ship_sheet.png", Vec2::new(32.0, 32.0)),
+        ("explosion", "resources/explosion.png", Vec2::new(32.0, 32.0)),
+    ];
+    for (name, path, frame_size) in sprite_sheets {
+        texture_store.load_sprite_sheet(&mut texture_map, name, path, frame_size).await;
+    }
+
+    // Each power up has its own texture, named after the power up it represents
+    for kind in ALL_POWER_UPS {
+        texture_store.load(&mut texture_map, kind.texture_name(), &format!("resources/{}.png", kind.texture_name())).await;
+    }
+
+    // The stars in the background are single white pixels, scaled and dimmed to suit each one
+    texture_store.insert(&mut texture_map, "star", Texture2D::from_rgba8(1, 1, &[255, 255, 255, 255]));
+
+    // Load the definitions for every ship the player can pick from. There's no game without a ship to fly, so unlike
+    // the settings, these have to load
+    let ship_classes = ships::load_ship_classes("resources/ships")
+        .unwrap_or_else(|err| panic!("Couldn't load the ship definitions: {}", err));
+
+    // The settings are optional, so if they can't be loaded just go with the defaults
+    let settings = config::load_settings("resources/settings.cfg").unwrap_or_else(|err| {
+        warn!("Couldn't load settings, using the defaults: {}", err);
+        Settings::default()
+    });
 
     // Init our game manager to the main menu state, the previous state will also reflect this
-    let (world, resources) = new_game(&mut rng, &texture_map, &large_asteroid_textures);
+    let (world, resources) = new_game(&mut rng, &texture_map, &texture_store, &settings, ship_classes, GameRules::default(), RenderMode::default(), DebugOverlay::from_args(std::env::args()));
     let mut game_manager = GameStateManager{
         current_state: GameState::MainMenu,
         previous_state: GameState::MainMenu,
@@ -148,129 +355,78 @@ async fn main() {
     let mut input_manager = InputManager{};
 
     let mut schedule = Schedule::builder()
+        .add_system(steer_homing_missiles_system())
+        .add_system(apply_ship_physics_system())
         .add_system(apply_velocity_system())
         .add_system(rotate_asteroids_system())
         .add_system(destroy_timed_entities_system())
         .add_system(handle_bullet_collisions_system())
         .add_system(handle_player_collision_system())
+        .add_system(respawn_player_system())
+        .add_system(expire_invulnerability_system())
+        .add_system(wave_progression_system())
+        .add_system(spawn_saucers_system())
+        .add_system(saucer_ai_system())
+        .add_system(collect_power_ups_system())
+        .add_system(expire_power_up_effects_system())
+        .add_system(fire_weapons_system())
+        .add_system(recharge_shields_system())
+        .add_system(expire_damage_flashes_system())
+        .add_system(hyperspace_jump_system())
+        .add_system(finish_hyperspace_system())
+        .add_system(animate_engines_system())
+        .add_system(animate_sprites_system())
+        .add_system(update_particles_system())
+        .add_system(emit_particles_system())
+        .add_system(apply_camera_kicks_system())
+        .add_system(decay_combo_system())
+        .add_system(award_milestones_system())
         .build();
 
-    let mut final_score = 0;
+    let mut renderer = Renderer::new(&settings);
 
     loop {
         clear_background(BLACK);
 
-        // Update the time resource on each tick
-        game_manager.resources.remove::<TimeResource>();
-        game_manager.resources.insert(TimeResource{absolute_time:get_time()});
+        // The window can be resized at any time, so keep track of how big it is now
+        if let Some(mut screen_dimensions) = game_manager.resources.get_mut::<ScreenDimensions>() {
+            screen_dimensions.width = screen_width();
+            screen_dimensions.height = screen_height();
+        }
 
-        // Grab any input that is present for this frame, and map it to a valid action, if any
-        let current_actions = input_manager.map_input();
+        // A hit-stop freezes the action for a moment, so nothing in the game should move, including the player's ship
+        let hit_stopped = matches!(game_manager.current_state, GameState::GamePlay)
+            && game_manager.resources.get_mut::<CameraEffects>()
+                .is_some_and(|mut camera_effects| camera_effects.update_hit_stop(get_frame_time() as f64));
 
-        // Handle the action for the current controlset. If an invalid action is provided, ignore
-        if let Some(new_state) = game_manager.active_controls.execute_action(current_actions, &mut game_manager.world, &texture_map) {
-            game_manager.update_state(new_state);
+        // Update the game clock on each tick, holding it still for as long as the action is frozen
+        if let Some(mut time_resource) = game_manager.resources.get_mut::<TimeResource>() {
+            if hit_stopped {
+                time_resource.frozen_time += get_frame_time() as f64;
+            }
+            time_resource.absolute_time = get_time() - time_resource.frozen_time;
         }
 
-        match game_manager.current_state {
-            GameState::MainMenu => {
-                let title = "MQ Asteroids";
-                let instructions = "Press <ENTER> to start";
-                draw_text_ex(
-                    title,
-                    screen_width() / 2. - measure_text(title, None, 50, 1.0).width / 2.0,
-                    screen_height() / 2.,
-                    TextParams{
-                        font_size: 50,
-                        color: WHITE,
-                        ..Default::default()
-                    });
-                draw_text_ex(
-                    instructions,
-                    screen_width() / 2. - measure_text(instructions, None, 30, 1.0).width / 2.0,
-                    screen_height() / 2. + 50.,
-                    TextParams{
-                        font_size: 30,
-                        color: WHITE,
-                        ..Default::default()
-                    });
-            }
-            GameState::GamePlay => {
-                // If we got after a previous game ending, reset everything
+        // Grab any input that is present for this frame, and map it to a valid action, if any
+        let current_actions = input_manager.map_input();
 
+        // Handle the action for the current controlset. If an invalid action is provided, ignore. Any input during a
+        // hit-stop is dropped, rather than piling up and all landing on the first frame after it
+        if !hit_stopped {
+            if let Some(new_state) = game_manager.active_controls.execute_action(current_actions, &mut game_manager.world, &mut game_manager.resources) {
+                game_manager.update_state(new_state);
+            }
+        }
 
-                // Execute all systems
+        // Execute all systems while the game is being played, unless a hit-stop has frozen the action
+        if let GameState::GamePlay = game_manager.current_state {
+            if !hit_stopped {
                 schedule.execute(&mut game_manager.world, &mut game_manager.resources);
-
-                let mut render_data = Vec::new();
-                let mut query = <&DrawableComponent>::query();
-                for drawable in query.iter(&game_manager.world) {
-                    render_data.push(RenderData {
-                        position: drawable.position.clone(),
-                        rotation: drawable.rotation.clone(),
-                        texture: drawable.texture_id.clone()
-                    });
-                }
-
-                for data in render_data {
-                    let draw_params = DrawTextureParams{
-                        rotation: data.rotation,
-                        ..Default::default()
-                    };
-
-                    draw_texture_ex(&texture_assets.get(&data.texture).unwrap(), data.position.x, data.position.y, WHITE, draw_params);
-                }
-
-                // Draw the players score to the top of the screen
-                if let Some(score_resource) = game_manager.resources.get::<ScoreResource>() {
-                    let score_text = format!("{}", score_resource.score.to_string());
-                    draw_text_ex(
-                        &score_text,
-                        screen_width() / 2. - measure_text(&score_text, None, 50, 1.0).width / 2.0,
-                        measure_text(&score_text, None, 50, 1.0).height + 5.0,
-                        TextParams{
-                            font_size: 50,
-                            color: WHITE,
-                            ..Default::default()
-                        });
-                }
-            }
-            GameState::Pause => {
-                let title = "Game Paused";
-                draw_text_ex(
-                    title,
-                    screen_width() / 2. - measure_text(title, None, 50, 1.0).width / 2.0,
-                    screen_height() / 2.,
-                    TextParams{
-                        font_size: 50,
-                        color: WHITE,
-                        ..Default::default()
-                    });
-            }
-            GameState::GameOver => {
-                let title = "GAME OVER";
-                draw_text_ex(
-                    title,
-                    screen_width() / 2. - measure_text(title, None, 50, 1.0).width / 2.0,
-                    screen_height() / 2.,
-                    TextParams{
-                        font_size: 50,
-                        color: WHITE,
-                        ..Default::default()
-                    });
-                let score_text = format!("Your score was: {}", final_score.to_string());
-                draw_text_ex(
-                    &score_text,
-                    screen_width() / 2. - measure_text(&score_text, None, 30, 1.0).width / 2.0,
-                    screen_height() / 2. + 50.,
-                    TextParams{
-                        font_size: 30,
-                        color: WHITE,
-                        ..Default::default()
-                    });
             }
         }
 
+        renderer.render(&game_manager.current_state, &mut game_manager.world, &mut game_manager.resources);
+
         // Check if the player has lost (ship collided with an asteroid)
         let game_over = {
             if let Some(game_over_resource) = game_manager.resources.get::<GameOverResource>() {
@@ -283,56 +439,78 @@ async fn main() {
         if game_over {
             game_manager.update_state(GameState::GameOver);
              //  Set the final score, and reset everything
-            final_score = game_manager.resources.get::<ScoreResource>().unwrap().score;
-
-            let (world, resources) = new_game(&mut rng, &texture_map, &large_asteroid_textures);
+            let final_score = game_manager.resources.get::<ScoreResource>().unwrap().score;
+
+            // The player keeps the ship class, rules and graphics they picked for the next game, and the debug overlay
+            // stays however it was left
+            let ship_classes = game_manager.resources.remove::<ShipClasses>().unwrap();
+            let rules = game_manager.resources.remove::<GameRules>().unwrap();
+            let render_mode = game_manager.resources.remove::<RenderMode>().unwrap();
+            let debug_overlay = game_manager.resources.remove::<DebugOverlay>().unwrap();
+            let (world, resources) = new_game(&mut rng, &texture_map, &texture_store, &settings, ship_classes, rules, render_mode, debug_overlay);
             game_manager.world = world;
             game_manager.resources = resources;
+            game_manager.resources.insert(FinalScoreResource{score: final_score});
         }
 
         next_frame().await;
     }
 }
 
-fn new_game(rng: &mut ThreadRng, texture_map: &TextureMap, large_asteroid_textures: &Vec<Uuid>) -> (World, Resources) {
+#[allow(clippy::too_many_arguments)]
+fn new_game(rng: &mut ThreadRng, texture_map: &TextureMap, texture_store: &TextureStore, settings: &Settings, ship_classes: ShipClasses, rules: GameRules, render_mode: RenderMode, debug_overlay: DebugOverlay) -> (World, Resources) {
     // Create our legion world, and any shared resources our systems will need
     let mut world = World::default();
 
     let mut resources = Resources::default();
-    resources.insert(ScreenDimensions{width: screen_width(), height: screen_height()});
     resources.insert(texture_map.clone());
-    resources.insert(TimeResource{absolute_time: get_time()});
-    resources.insert(ScoreResource{score: 0});
+    resources.insert(texture_store.clone());
+    resources.insert(RenderQueue::default());
+    resources.insert(TimeResource{absolute_time: get_time(), frozen_time: 0.0});
+    resources.insert(ScoreResource::default());
     resources.insert(GameOverResource{game_over: false});
-
-    // Load our player entity into the world
-    let ship_position = Vec2::new(screen_width() / 2., screen_height() / 2.);
-    let ship_texture_id = texture_map.mapping.get("ship").unwrap();
-    world.push(
-        (
-            PlayerComponent{last_bullet_fired: 0.0, fire_rate: 0.2},
-            DrawableComponent{texture_id: *ship_texture_id, position: ship_position, rotation: 0.0},
-            VelocityComponent{velocity: Vec2::new(0.0, 0.0)},
-            CollisionComponent{rect: Rect::new(ship_position[0], ship_position[1], 16., 16.), collided: false},
-        )
-    );
-
-    // Add eight large asteroids, and set them moving in random directions, at random velocity
-    for _ in 0..12 {
-        let rotation = rng.gen_range(-10.0..=10.0);
-        let pos = Vec2::new(rng.gen_range(0.0..=screen_width()), rng.gen_range(0.0..=screen_height()));
-        let tex_uuid = large_asteroid_textures.choose().unwrap();
-
-        world.push(
-            (
-                AsteroidComponent{is_large: true},
-                DrawableComponent{texture_id: *tex_uuid, position: pos, rotation: rotation},
-                VelocityComponent{velocity: Vec2::from_angle(rotation) * rng.gen_range(0.1..=1.0)},
-                CollisionComponent{rect: Rect::new(pos[0], pos[1], 16., 16.), collided: false},
-                ScoreComponent{value: 5},
-            )
-        );
-    }
+    resources.insert(LivesResource{lives: STARTING_LIVES});
+    resources.insert(RespawnResource{respawn_pending: false, died_at: f64::NEG_INFINITY});
+    resources.insert(WaveResource{wave: 1, interstitial_started_at: None});
+    resources.insert(SaucerSpawnResource{next_spawn_at: get_time() + FIRST_SAUCER_DELAY});
+    resources.insert(ParticlePool::new(MAX_PARTICLES));
+    resources.insert(CameraEffects::default());
+    resources.insert(Starfield::generate(rng));
+
+    // Load our player entity into the world, in the middle of the playfield, with the camera looking at it
+    let playfield = settings.playfield;
+    let ship_position = playfield.size() / 2.0;
+    resources.insert(GameCamera::new(ship_position));
+    let ship_texture_id = texture_map.mapping.get("ship_sheet").unwrap();
+    let mut cmd = CommandBuffer::new(&world);
+    entities::spawn_player(&mut cmd, ship_position, *ship_texture_id, ship_classes.selected().physics, get_time());
+    cmd.flush(&mut world, &mut resources);
+
+    // Add the first wave of large asteroids, keeping them clear of the player's ship
+    let difficulty_curve = settings.difficulty;
+    world.extend(entities::asteroid_wave(rng, 1, &difficulty_curve, &playfield, texture_map, Some(ship_position)));
+
+    resources.insert(playfield);
+    resources.insert(ScreenDimensions{width: screen_width(), height: screen_height()});
+    resources.insert(difficulty_curve);
+    resources.insert(ScoreMilestones::new(settings.extra_life_interval));
+    resources.insert(ship_classes);
+    resources.insert(rules);
+    resources.insert(render_mode);
+    resources.insert(debug_overlay);
 
     (world, resources)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asteroid_count_is_capped_even_when_the_curve_would_overflow() {
+        let curve = DifficultyCurve{asteroids_per_wave: u32::MAX, max_asteroid_count: 30, ..DifficultyCurve::default()};
+        assert_eq!(curve.asteroid_count(1), curve.base_asteroid_count);
+        assert_eq!(curve.asteroid_count(3), 30);
+        assert_eq!(curve.asteroid_count(u32::MAX), 30);
+    }
+}
@@ -0,0 +1,50 @@
+use std::f32::consts::TAU;
+use std::ops::RangeInclusive;
+use macroquad::math::Vec2;
+use rand::Rng;
+
+/// The player's ship, an arrowhead pointing along its heading with a notch cut out of the back
+pub const SHIP_OUTLINE: [Vec2; 4] = [
+    Vec2::new(12.0, 0.0),
+    Vec2::new(-10.0, -8.0),
+    Vec2::new(-6.0, 0.0),
+    Vec2::new(-10.0, 8.0),
+];
+
+/// A flying saucer, with a domed cockpit on top
+pub const SAUCER_OUTLINE: [Vec2; 8] = [
+    Vec2::new(-14.0, 0.0),
+    Vec2::new(-8.0, -4.0),
+    Vec2::new(-4.0, -8.0),
+    Vec2::new(4.0, -8.0),
+    Vec2::new(8.0, -4.0),
+    Vec2::new(14.0, 0.0),
+    Vec2::new(8.0, 6.0),
+    Vec2::new(-8.0, 6.0),
+];
+
+/// A bullet, which is just a tiny diamond
+pub const BULLET_OUTLINE: [Vec2; 4] = [
+    Vec2::new(2.0, 0.0),
+    Vec2::new(0.0, -1.5),
+    Vec2::new(-2.0, 0.0),
+    Vec2::new(0.0, 1.5),
+];
+
+/// How many points go around the edge of an asteroid
+const ASTEROID_OUTLINE_POINTS: RangeInclusive<usize> = 9..=13;
+
+/// How far each point around the edge of an asteroid sits from its center, as a fraction of its radius
+const ASTEROID_OUTLINE_JAGGEDNESS: RangeInclusive<f32> = 0.7..=1.0;
+
+/// Makes up a lumpy, jagged outline for an asteroid that is at most `radius` across from its center to its edge. Every
+/// asteroid gets an outline of its own, so no two look the same
+pub fn asteroid_outline(rng: &mut impl Rng, radius: f32) -> Vec<Vec2> {
+    let point_count = rng.gen_range(ASTEROID_OUTLINE_POINTS);
+    (0..point_count)
+        .map(|index| {
+            let angle = index as f32 / point_count as f32 * TAU;
+            Vec2::from_angle(angle) * radius * rng.gen_range(ASTEROID_OUTLINE_JAGGEDNESS)
+        })
+        .collect()
+}
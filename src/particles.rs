@@ -0,0 +1,136 @@
+use std::ops::RangeInclusive;
+use macroquad::color::{Color, LIGHTGRAY, ORANGE, WHITE, YELLOW};
+use macroquad::math::Vec2;
+use rand::Rng;
+
+/// The most particles that can be alive at once. Anything emitted while the pool is full is simply dropped
+pub const MAX_PARTICLES: usize = 1000;
+
+/// Describes what the particles from one kind of effect look like, and how they move
+#[derive(Debug, PartialEq)]
+pub struct ParticleEffect {
+    /// How many particles are emitted at a time
+    pub count: RangeInclusive<u32>,
+    /// How fast (in pixels per frame) particles fly away from where they were emitted
+    pub speed: RangeInclusive<f32>,
+    /// How far either side of the emitter's direction (in radians) particles can head off in. A spread of PI sends
+    /// them out in every direction
+    pub spread: f32,
+    /// How long, in seconds, particles live for
+    pub lifetime: RangeInclusive<f64>,
+    /// Particles fade from their start color and size to their end color and size over their lifetime
+    pub start_color: Color,
+    pub end_color: Color,
+    pub start_size: f32,
+    pub end_size: f32,
+}
+
+/// Dust and chunks of rock left behind by a destroyed asteroid
+pub const ASTEROID_DEBRIS: ParticleEffect = ParticleEffect{
+    count: 12..=20,
+    speed: 0.5..=2.5,
+    spread: std::f32::consts::PI,
+    lifetime: 0.4..=1.0,
+    start_color: LIGHTGRAY,
+    end_color: Color::new(0.31, 0.31, 0.31, 0.0),
+    start_size: 2.5,
+    end_size: 0.5,
+};
+
+/// A fireball, for when a ship is destroyed
+pub const SHIP_EXPLOSION: ParticleEffect = ParticleEffect{
+    count: 40..=60,
+    speed: 0.5..=4.0,
+    spread: std::f32::consts::PI,
+    lifetime: 0.5..=1.5,
+    start_color: YELLOW,
+    end_color: Color::new(0.9, 0.16, 0.22, 0.0),
+    start_size: 3.0,
+    end_size: 1.0,
+};
+
+/// Exhaust streaming out of the back of a ship while it is thrusting
+pub const ENGINE_EXHAUST: ParticleEffect = ParticleEffect{
+    count: 1..=2,
+    speed: 1.0..=2.0,
+    spread: 0.3,
+    lifetime: 0.15..=0.3,
+    start_color: ORANGE,
+    end_color: Color::new(0.9, 0.16, 0.22, 0.0),
+    start_size: 2.0,
+    end_size: 0.5,
+};
+
+/// A quick spray of sparks where a shot hits something
+pub const BULLET_IMPACT: ParticleEffect = ParticleEffect{
+    count: 4..=8,
+    speed: 1.0..=3.0,
+    spread: 0.8,
+    lifetime: 0.1..=0.25,
+    start_color: WHITE,
+    end_color: Color::new(0.51, 0.51, 0.51, 0.0),
+    start_size: 1.5,
+    end_size: 0.5,
+};
+
+/// A single particle, which isn't an entity, so thousands of them don't clog up the world
+#[derive(Clone, Copy, Debug)]
+pub struct Particle {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub created_at: f64,
+    pub lifetime: f64,
+    pub effect: &'static ParticleEffect,
+}
+
+impl Particle {
+    /// How far through its life the particle is, from 0.0 when it is emitted to 1.0 when it expires
+    pub fn age(&self, time: f64) -> f32 {
+        ((time - self.created_at) / self.lifetime).clamp(0.0, 1.0) as f32
+    }
+
+    pub fn color(&self, time: f64) -> Color {
+        let age = self.age(time);
+        let (start, end) = (self.effect.start_color, self.effect.end_color);
+        Color::new(
+            start.r + (end.r - start.r) * age,
+            start.g + (end.g - start.g) * age,
+            start.b + (end.b - start.b) * age,
+            start.a + (end.a - start.a) * age,
+        )
+    }
+
+    pub fn size(&self, time: f64) -> f32 {
+        self.effect.start_size + (self.effect.end_size - self.effect.start_size) * self.age(time)
+    }
+}
+
+/// Every live particle. Its storage is allocated up front, and never grows past `max_particles`
+pub struct ParticlePool {
+    pub particles: Vec<Particle>,
+    pub max_particles: usize,
+}
+
+impl ParticlePool {
+    pub fn new(max_particles: usize) -> Self {
+        ParticlePool{particles: Vec::with_capacity(max_particles), max_particles}
+    }
+
+    /// Emits a batch of particles from the given effect at `position`, heading off around `direction`
+    pub fn emit(&mut self, rng: &mut impl Rng, effect: &'static ParticleEffect, position: Vec2, direction: f32, time: f64) {
+        for _ in 0..rng.gen_range(effect.count.clone()) {
+            if self.particles.len() >= self.max_particles {
+                return;
+            }
+
+            let angle = direction + rng.gen_range(-effect.spread..=effect.spread);
+            self.particles.push(Particle{
+                position,
+                velocity: Vec2::from_angle(angle) * rng.gen_range(effect.speed.clone()),
+                created_at: time,
+                lifetime: rng.gen_range(effect.lifetime.clone()),
+                effect,
+            });
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use macroquad::prelude::*;
+use crate::config::Settings;
+use crate::render::Canvas;
+
+/// Every post processing pass draws the frame as a single textured quad, so they can all share this vertex shader
+const VERTEX_SHADER: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}
+"#;
+
+/// Spreads anything bright out over the pixels around it, and adds that on top of the frame
+const BLOOM_SHADER: &str = r#"#version 100
+precision lowp float;
+
+varying vec4 color;
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 TextureSize;
+
+void main() {
+    vec3 base = texture2D(Texture, uv).rgb;
+    vec3 glow = vec3(0.0);
+    for (int x = -2; x <= 2; x++) {
+        for (int y = -2; y <= 2; y++) {
+            vec2 offset = vec2(float(x), float(y)) * 1.5 / TextureSize;
+            glow += max(texture2D(Texture, uv + offset).rgb - 0.5, 0.0);
+        }
+    }
+    gl_FragColor = vec4(base + glow / 12.5, 1.0);
+}
+"#;
+
+/// Pulls the red and blue channels apart, further the closer they are to the edges of the frame
+const CHROMATIC_ABERRATION_SHADER: &str = r#"#version 100
+precision lowp float;
+
+varying vec4 color;
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 TextureSize;
+
+void main() {
+    vec2 offset = (uv - 0.5) * 0.006;
+    float r = texture2D(Texture, uv + offset).r;
+    float g = texture2D(Texture, uv).g;
+    float b = texture2D(Texture, uv - offset).b;
+    gl_FragColor = vec4(r, g, b, 1.0);
+}
+"#;
+
+/// Darkens every other row of pixels, and the corners of the frame, like an old CRT monitor
+const SCANLINES_SHADER: &str = r#"#version 100
+precision lowp float;
+
+varying vec4 color;
+varying vec2 uv;
+
+uniform sampler2D Texture;
+uniform vec2 TextureSize;
+
+void main() {
+    vec3 base = texture2D(Texture, uv).rgb;
+    float scanline = 0.75 + 0.25 * abs(sin(uv.y * TextureSize.y * 3.14159));
+    vec2 from_center = uv - 0.5;
+    float vignette = 1.0 - dot(from_center, from_center) * 0.8;
+    gl_FragColor = vec4(base * scanline * vignette, 1.0);
+}
+"#;
+
+/// One of the effects that can be applied over the whole frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PostEffect {
+    Bloom,
+    ChromaticAberration,
+    Scanlines,
+}
+
+impl PostEffect {
+    fn name(&self) -> &'static str {
+        match self {
+            PostEffect::Bloom => "bloom",
+            PostEffect::ChromaticAberration => "chromatic aberration",
+            PostEffect::Scanlines => "scanlines",
+        }
+    }
+
+    fn fragment_shader(&self) -> &'static str {
+        match self {
+            PostEffect::Bloom => BLOOM_SHADER,
+            PostEffect::ChromaticAberration => CHROMATIC_ABERRATION_SHADER,
+            PostEffect::Scanlines => SCANLINES_SHADER,
+        }
+    }
+
+    /// The effects switched on in the settings, in the order they are applied. The scanlines go on last, so that
+    /// nothing blurs or smears them
+    fn enabled(settings: &Settings) -> Vec<PostEffect> {
+        [
+            (PostEffect::Bloom, settings.bloom),
+            (PostEffect::ChromaticAberration, settings.chromatic_aberration),
+            (PostEffect::Scanlines, settings.scanlines),
+        ]
+            .into_iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(effect, _)| effect)
+            .collect()
+    }
+}
+
+/// A chain of effects applied over the finished frame, one after another, before it is put up in the window. Any effect
+/// whose shader can't be built on this machine is left out of the chain, so with no working shaders at all the frame
+/// goes up untouched
+pub struct PostProcessing {
+    passes: Vec<Material>,
+    /// Each pass reads the frame from one of these and draws it onto the other, so they take it in turns
+    targets: [RenderTarget; 2],
+}
+
+impl PostProcessing {
+    /// Builds the chain of effects switched on in the settings
+    pub fn new(settings: &Settings) -> Self {
+        let passes = PostEffect::enabled(settings)
+            .into_iter()
+            .filter_map(|effect| {
+                let material = load_material(
+                    ShaderSource::Glsl{vertex: VERTEX_SHADER, fragment: effect.fragment_shader()},
+                    MaterialParams{
+                        uniforms: vec![UniformDesc::new("TextureSize", UniformType::Float2)],
+                        ..Default::default()
+                    },
+                );
+                match material {
+                    Ok(material) => Some(material),
+                    Err(err) => {
+                        warn!("Couldn't set up the {} effect, so it will be skipped: {}", effect.name(), err);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        let targets = [(); 2].map(|_| {
+            let target = render_target(Canvas::size().x as u32, Canvas::size().y as u32);
+            target.texture.set_filter(FilterMode::Nearest);
+            target
+        });
+        PostProcessing{passes, targets}
+    }
+
+    /// Runs the frame through every effect in the chain, and hands back the finished frame
+    pub fn apply(&self, frame: &Texture2D) -> Texture2D {
+        let mut frame = frame.clone();
+        for (index, material) in self.passes.iter().enumerate() {
+            let target = &self.targets[index % 2];
+            set_camera(&Camera2D{
+                target: Canvas::size() / 2.0,
+                zoom: Vec2::new(2.0 / Canvas::size().x, 2.0 / Canvas::size().y),
+                render_target: Some(target.clone()),
+                ..Default::default()
+            });
+            clear_background(BLACK);
+
+            material.set_uniform("TextureSize", Canvas::size());
+            gl_use_material(material);
+            draw_texture_ex(&frame, 0.0, 0.0, WHITE, DrawTextureParams{dest_size: Some(Canvas::size()), ..Default::default()});
+            gl_use_default_material();
+
+            frame = target.texture.clone();
+        }
+        frame
+    }
+}
@@ -0,0 +1,54 @@
+use crate::components::PowerUpKind;
+
+/// The chance that an asteroid shot down by the player leaves a power up behind
+pub const POWER_UP_DROP_CHANCE: f64 = 0.1;
+
+/// How long, in seconds, a dropped power up floats around before disappearing if nobody collects it
+pub const POWER_UP_LIFETIME: f64 = 8.0;
+
+/// Every kind of power up that can be dropped, each one is equally likely
+pub const ALL_POWER_UPS: [PowerUpKind; 4] = [
+    PowerUpKind::SpreadShot,
+    PowerUpKind::RapidFire,
+    PowerUpKind::Shield,
+    PowerUpKind::ExtraLife,
+];
+
+/// How much shorter the ship's weapon cooldown is while rapid fire is active
+pub const RAPID_FIRE_COOLDOWN_MULTIPLIER: f64 = 0.4;
+
+/// The angle, in radians, between each of the bullets fired while spread shot is active
+pub const SPREAD_SHOT_ANGLE: f32 = 0.2;
+
+impl PowerUpKind {
+    /// Name (in the texture map) of the texture the pickup is drawn with
+    pub fn texture_name(&self) -> &'static str {
+        match self {
+            PowerUpKind::SpreadShot => "power_up_spread_shot",
+            PowerUpKind::RapidFire => "power_up_rapid_fire",
+            PowerUpKind::Shield => "power_up_shield",
+            PowerUpKind::ExtraLife => "power_up_extra_life",
+        }
+    }
+
+    /// How long, in seconds, the effect lasts once collected. Effects that are applied once, and then done with, have
+    /// no duration
+    pub fn duration(&self) -> Option<f64> {
+        match self {
+            PowerUpKind::SpreadShot => Some(10.0),
+            PowerUpKind::RapidFire => Some(8.0),
+            PowerUpKind::Shield => Some(6.0),
+            PowerUpKind::ExtraLife => None,
+        }
+    }
+
+    /// What the effect is called when it is shown in the HUD
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerUpKind::SpreadShot => "SPREAD SHOT",
+            PowerUpKind::RapidFire => "RAPID FIRE",
+            PowerUpKind::Shield => "SHIELD",
+            PowerUpKind::ExtraLife => "EXTRA LIFE",
+        }
+    }
+}
@@ -0,0 +1,631 @@
+use std::collections::HashMap;
+use legion::{system, IntoQuery, Resources, Schedule, World};
+use legion::world::SubWorld;
+use macroquad::prelude::*;
+use uuid::Uuid;
+use crate::animation::SpriteSheet;
+use crate::camera::{CameraEffects, CameraMode, GameCamera};
+use crate::config::Settings;
+use crate::debug::draw_debug_overlay;
+use crate::components::{ActiveEffectsComponent, DamageFlashComponent, DrawableComponent, HyperspaceComponent, InvulnerableComponent, LaserBeamComponent, MilestoneReachedComponent, OutlineComponent, PlayerComponent, PowerUpKind, RenderLayer, ScorePopupComponent, ShieldComponent, TimedExistenceComponent, WeaponComponent};
+use crate::outlines::SHIP_OUTLINE;
+use crate::particles::ParticlePool;
+use crate::post_processing::PostProcessing;
+use crate::ships::ShipClasses;
+use crate::starfield::Starfield;
+use crate::{FinalScoreResource, GameRules, GameState, LivesResource, PlayfieldDimensions, ScoreResource, ScreenDimensions, TextureMap, TimeResource, WaveResource};
+
+/// The size, in pixels, of the canvas everything is drawn onto. However big the window is, the canvas is scaled up or
+/// down to fit it, so the game looks the same at any resolution, and everything can be laid out in canvas pixels
+pub const CANVAS_WIDTH: f32 = 640.0;
+pub const CANVAS_HEIGHT: f32 = 480.0;
+
+/// Size of the shield bar shown in the HUD
+const SHIELD_BAR_WIDTH: f32 = 100.0;
+const SHIELD_BAR_HEIGHT: f32 = 10.0;
+
+/// How long, in seconds, an invulnerable entity spends visible (and then hidden) while blinking
+const INVULNERABLE_BLINK_INTERVAL: f64 = 0.1;
+
+/// How far, in pixels, score popups float upwards over their lifetime
+const SCORE_POPUP_RISE: f32 = 30.0;
+
+/// How long, in seconds, the milestone announcement spends in each color while flashing
+const MILESTONE_FLASH_INTERVAL: f64 = 0.15;
+
+/// How thick the lines are that outlines are drawn with in vector mode
+const OUTLINE_THICKNESS: f32 = 1.5;
+
+/// Width and height of the placeholder texture
+const PLACEHOLDER_SIZE: u16 = 16;
+
+/// Every texture that has been loaded, by id. Asking for a texture that never loaded gets a bright magenta placeholder
+/// instead, so a missing texture is easy to spot without bringing the whole game down. Textures loaded as sprite sheets
+/// also keep track of how they are cut up into frames
+#[derive(Clone)]
+pub struct TextureStore {
+    textures: HashMap<Uuid, Texture2D>,
+    sheets: HashMap<Uuid, SpriteSheet>,
+    placeholder: Texture2D,
+}
+
+impl Default for TextureStore {
+    fn default() -> Self {
+        let pixels = [255, 0, 255, 255].repeat(PLACEHOLDER_SIZE as usize * PLACEHOLDER_SIZE as usize);
+        TextureStore{textures: HashMap::new(), sheets: HashMap::new(), placeholder: Texture2D::from_rgba8(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, &pixels)}
+    }
+}
+
+impl TextureStore {
+    /// Loads the texture at `path`, and makes it available under `name` in the texture map. The name is registered
+    /// even if the texture fails to load, so anything using it is drawn with the placeholder
+    pub async fn load(&mut self, texture_map: &mut TextureMap, name: &str, path: &str) {
+        let texture_id = Uuid::new_v4();
+        texture_map.mapping.insert(name.to_string(), texture_id);
+        match load_texture(path).await {
+            Ok(texture) => {
+                self.textures.insert(texture_id, texture);
+            }
+            Err(err) => warn!("Couldn't load texture {} from {}: {}", name, path, err),
+        }
+    }
+
+    /// Makes a texture that was made in code, rather than loaded from a file, available under `name` in the texture map
+    pub fn insert(&mut self, texture_map: &mut TextureMap, name: &str, texture: Texture2D) {
+        let texture_id = Uuid::new_v4();
+        texture_map.mapping.insert(name.to_string(), texture_id);
+        self.textures.insert(texture_id, texture);
+    }
+
+    /// Loads the texture at `path` as a sprite sheet, cut up into frames of `frame_size`. If the texture fails to load,
+    /// anything using it is drawn with the placeholder, as a single frame
+    pub async fn load_sprite_sheet(&mut self, texture_map: &mut TextureMap, name: &str, path: &str, frame_size: Vec2) {
+        self.load(texture_map, name, path).await;
+        let texture_id = texture_map.mapping[name];
+        if let Some(texture) = self.textures.get(&texture_id) {
+            self.sheets.insert(texture_id, SpriteSheet::new(texture.size(), frame_size));
+        }
+    }
+
+    pub fn get(&self, texture_id: &Uuid) -> &Texture2D {
+        self.textures.get(texture_id).unwrap_or(&self.placeholder)
+    }
+
+    /// Looks up a texture by the name it was loaded under
+    pub fn get_named(&self, texture_map: &TextureMap, name: &str) -> &Texture2D {
+        texture_map.mapping.get(name).map_or(&self.placeholder, |texture_id| self.get(texture_id))
+    }
+
+    /// How big a sprite drawn with the texture is, which is a single frame for sprite sheets, or the whole texture
+    /// otherwise
+    pub fn sprite_size(&self, texture_id: &Uuid) -> Vec2 {
+        self.sheets.get(texture_id).map_or_else(|| self.get(texture_id).size(), |sheet| sheet.frame_size)
+    }
+
+    /// Which part of the texture to draw for the given frame, or `None` to draw the whole thing
+    pub fn frame(&self, texture_id: &Uuid, frame: usize) -> Option<Rect> {
+        self.sheets.get(texture_id).map(|sheet| sheet.frame(frame))
+    }
+}
+
+/// A fixed size render target that the whole game is drawn onto, before it is scaled up to fit the window
+#[derive(Clone)]
+pub struct Canvas {
+    target: RenderTarget,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        let target = render_target(CANVAS_WIDTH as u32, CANVAS_HEIGHT as u32);
+        target.texture.set_filter(FilterMode::Nearest);
+        Canvas{target}
+    }
+}
+
+impl Canvas {
+    pub fn size() -> Vec2 {
+        Vec2::new(CANVAS_WIDTH, CANVAS_HEIGHT)
+    }
+
+    /// A macroquad camera that draws onto the canvas, filling it with `view_size` units of the world around `target`
+    pub fn camera(&self, target: Vec2, view_size: Vec2) -> Camera2D {
+        Camera2D{
+            target,
+            zoom: Vec2::new(2.0 / view_size.x, 2.0 / view_size.y),
+            render_target: Some(self.target.clone()),
+            ..Default::default()
+        }
+    }
+
+    /// A camera for drawing straight onto the canvas in canvas pixels, like the menus and the HUD are
+    pub fn ui_camera(&self) -> Camera2D {
+        self.camera(Canvas::size() / 2.0, Canvas::size())
+    }
+
+    /// The part of a window of the given size that the canvas is drawn into. This is the biggest area with the same
+    /// aspect ratio as the canvas that fits in the window, centered in it, with black bars filling in the rest
+    pub fn viewport(window_size: Vec2) -> Rect {
+        let scale = (window_size.x / CANVAS_WIDTH).min(window_size.y / CANVAS_HEIGHT);
+        let size = Canvas::size() * scale;
+        let top_left = (window_size - size) / 2.0;
+        Rect::new(top_left.x, top_left.y, size.x, size.y)
+    }
+
+    /// Runs the canvas through the post processing chain, then scales it to fit the window, and draws it there
+    pub fn present(&self, post_processing: &PostProcessing, screen_dimensions: &ScreenDimensions) {
+        let frame = post_processing.apply(&self.target.texture);
+        set_default_camera();
+        let viewport = Canvas::viewport(Vec2::new(screen_dimensions.width, screen_dimensions.height));
+        let draw_params = DrawTextureParams{
+            dest_size: Some(viewport.size()),
+            ..Default::default()
+        };
+        draw_texture_ex(&frame, viewport.x, viewport.y, WHITE, draw_params);
+    }
+}
+
+/// How the world is drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// With each entity's texture
+    #[default]
+    Sprites,
+    /// With line drawings of each entity's outline, like an old arcade vector display. Anything without an outline is
+    /// still drawn with its texture
+    Vector,
+}
+
+impl RenderMode {
+    pub fn toggle(&mut self) {
+        *self = match self {
+            RenderMode::Sprites => RenderMode::Vector,
+            RenderMode::Vector => RenderMode::Sprites,
+        };
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RenderMode::Sprites => "Sprites",
+            RenderMode::Vector => "Vector",
+        }
+    }
+}
+
+/// A sprite waiting to be drawn this frame, along with where the points of its outline are in the world, if it is
+/// being drawn as one
+struct RenderData {
+    drawable: DrawableComponent,
+    outline: Option<Vec<Vec2>>,
+    color: Color,
+}
+
+/// Everything that is going to be drawn this frame, sorted into the order it will be drawn in, along with how far the
+/// screen is being shaken
+#[derive(Default)]
+pub struct RenderQueue {
+    sprites: Vec<RenderData>,
+    shake: Vec2,
+}
+
+/// Draws the game, in whatever state it is in
+pub(crate) struct Renderer {
+    canvas: Canvas,
+    post_processing: PostProcessing,
+    gameplay: Schedule,
+}
+
+impl Renderer {
+    /// Sets up the renderer, with the post processing effects switched on in the settings
+    pub(crate) fn new(settings: &Settings) -> Self {
+        // Drawing has to happen on the main thread, so these all run as thread local systems, in the order they are
+        // added, from the bottom layer up
+        let gameplay = Schedule::builder()
+            .add_thread_local(update_camera_system())
+            .add_thread_local(queue_sprites_system())
+            .add_thread_local(draw_world_system())
+            .add_thread_local(draw_effects_system())
+            .add_thread_local(draw_hud_system())
+            .add_thread_local(draw_lives_system())
+            .build();
+        Renderer{canvas: Canvas::default(), post_processing: PostProcessing::new(settings), gameplay}
+    }
+
+    /// Draws a frame for the given state onto the canvas, and then puts the canvas up in the window
+    pub(crate) fn render(&mut self, state: &GameState, world: &mut World, resources: &mut Resources) {
+        // Every new game starts out with fresh resources, so make sure the canvas is there for the systems to draw on
+        if !resources.contains::<Canvas>() {
+            resources.insert(self.canvas.clone());
+        }
+
+        set_camera(&self.canvas.ui_camera());
+        clear_background(BLACK);
+        match state {
+            GameState::MainMenu => draw_main_menu(resources),
+            GameState::GamePlay => {
+                self.gameplay.execute(world, resources);
+                draw_debug_overlay(world, resources);
+            }
+            GameState::Pause => draw_pause(),
+            GameState::GameOver => draw_game_over(resources),
+        }
+
+        if let Some(screen_dimensions) = resources.get::<ScreenDimensions>() {
+            self.canvas.present(&self.post_processing, &screen_dimensions);
+        }
+    }
+}
+
+/// Draws `text` centered horizontally on the canvas
+fn draw_centered_text(text: &str, y: f32, font_size: u16, color: Color) {
+    draw_text_ex(
+        text,
+        CANVAS_WIDTH / 2. - measure_text(text, None, font_size, 1.0).width / 2.0,
+        y,
+        TextParams{
+            font_size,
+            color,
+            ..Default::default()
+        });
+}
+
+fn draw_main_menu(resources: &Resources) {
+    draw_centered_text("MQ Asteroids", CANVAS_HEIGHT / 2., 50, WHITE);
+    draw_centered_text("Press <ENTER> to start", CANVAS_HEIGHT / 2. + 50., 30, WHITE);
+
+    // Show which ship the player is going to fly, and how to pick another one
+    if let Some(ship_classes) = resources.get::<ShipClasses>() {
+        let ship_text = format!("Ship: {} (press <S> to change)", ship_classes.selected().name);
+        draw_centered_text(&ship_text, CANVAS_HEIGHT / 2. + 90., 20, WHITE);
+    }
+
+    // Show whether classic mode is switched on, and how to toggle it
+    if let Some(rules) = resources.get::<GameRules>() {
+        let classic_text = format!("Classic mode: {} (press <C> to toggle)", if rules.classic { "ON" } else { "OFF" });
+        draw_centered_text(&classic_text, CANVAS_HEIGHT / 2. + 115., 20, WHITE);
+    }
+
+    // Show how the world is going to be drawn, and how to switch it over
+    if let Some(render_mode) = resources.get::<RenderMode>() {
+        let graphics_text = format!("Graphics: {} (press <V> to toggle)", render_mode.label());
+        draw_centered_text(&graphics_text, CANVAS_HEIGHT / 2. + 140., 20, WHITE);
+    }
+}
+
+fn draw_pause() {
+    draw_centered_text("Game Paused", CANVAS_HEIGHT / 2., 50, WHITE);
+}
+
+fn draw_game_over(resources: &Resources) {
+    draw_centered_text("GAME OVER", CANVAS_HEIGHT / 2., 50, WHITE);
+    if let Some(final_score) = resources.get::<FinalScoreResource>() {
+        draw_centered_text(&format!("Your score was: {}", final_score.score), CANVAS_HEIGHT / 2. + 50., 30, WHITE);
+    }
+}
+
+/// Draws every queued sprite in the given layer, in the order they were queued
+fn draw_sprites(render_queue: &RenderQueue, layer: RenderLayer, texture_store: &TextureStore) {
+    for data in render_queue.sprites.iter().filter(|data| data.drawable.layer == layer) {
+        if let Some(outline) = &data.outline {
+            draw_outline(outline, data.color);
+            continue;
+        }
+
+        // Sprites are placed, and rotated, around their pivot, rather than their top left corner
+        let texture = texture_store.get(&data.drawable.texture_id);
+        let sprite_size = texture_store.sprite_size(&data.drawable.texture_id);
+        let top_left = data.drawable.top_left(sprite_size);
+        let draw_params = DrawTextureParams{
+            dest_size: Some(data.drawable.size(sprite_size)),
+            source: texture_store.frame(&data.drawable.texture_id, data.drawable.frame),
+            rotation: data.drawable.rotation,
+            flip_x: data.drawable.flip_x,
+            flip_y: data.drawable.flip_y,
+            pivot: Some(data.drawable.position),
+        };
+
+        draw_texture_ex(texture, top_left.x, top_left.y, data.color, draw_params);
+    }
+}
+
+/// Points the camera at whatever it should be looking at this frame
+#[system]
+#[read_component(PlayerComponent)]
+#[read_component(DrawableComponent)]
+pub fn update_camera(world: &SubWorld,
+                     #[resource] camera: &mut GameCamera,
+                     #[resource] starfield: &mut Starfield,
+                     #[resource] playfield: &PlayfieldDimensions) {
+    let focus = match camera.mode {
+        CameraMode::Fixed => playfield.size() / 2.0,
+        CameraMode::FollowPlayer => {
+            // Hold still while there is no ship to follow, like while waiting for the player to respawn
+            let mut player_query = <(&PlayerComponent, &DrawableComponent)>::query();
+            player_query.iter(world).next().map_or(camera.target, |(_, drawable)| drawable.position)
+        }
+    };
+    camera.look_at(focus, playfield.size());
+    starfield.follow_camera(camera.target);
+}
+
+/// Draws a closed outline through the given points
+fn draw_outline(points: &[Vec2], color: Color) {
+    for (start, end) in points.iter().zip(points.iter().cycle().skip(1)) {
+        draw_line(start.x, start.y, end.x, end.y, OUTLINE_THICKNESS, color);
+    }
+}
+
+#[system]
+#[read_component(DrawableComponent)]
+#[read_component(OutlineComponent)]
+#[read_component(InvulnerableComponent)]
+#[read_component(DamageFlashComponent)]
+#[read_component(HyperspaceComponent)]
+#[allow(clippy::too_many_arguments)]
+pub fn queue_sprites(world: &SubWorld,
+                     #[resource] render_queue: &mut RenderQueue,
+                     #[resource] camera_effects: &mut CameraEffects,
+                     #[resource] render_mode: &RenderMode,
+                     #[resource] camera: &GameCamera,
+                     #[resource] starfield: &Starfield,
+                     #[resource] texture_map: &TextureMap,
+                     #[resource] time_resource: &TimeResource) {
+    let frame_t = time_resource.absolute_time;
+
+    // Everything in the world (but not the HUD) is moved around a little to shake the screen
+    camera_effects.update(get_frame_time());
+    let shake = camera_effects.shake_offset(&mut ::rand::thread_rng());
+
+    render_queue.sprites.clear();
+    render_queue.shake = shake;
+    let mut query = <(&DrawableComponent, Option<&OutlineComponent>, Option<&InvulnerableComponent>, Option<&DamageFlashComponent>, Option<&HyperspaceComponent>)>::query();
+    for (drawable, outline, invulnerable, damage_flash, materializing) in query.iter(world) {
+        // Invulnerable entities blink, so skip drawing them every other interval
+        if let Some(invulnerable) = invulnerable {
+            if ((frame_t - invulnerable.started_at) / INVULNERABLE_BLINK_INTERVAL) as i64 % 2 == 1 {
+                continue;
+            }
+        }
+        // Anything that has just taken damage is tinted, so it's clear that the hit landed, and ships
+        // coming out of hyperspace fade in as they materialize
+        let mut color = if damage_flash.is_some() { RED } else { WHITE };
+        if let Some(materializing) = materializing {
+            color.a = ((frame_t - materializing.started_at) / materializing.duration).clamp(0.0, 1.0) as f32;
+        }
+        // The HUD stays put while everything else shakes
+        let offset = if drawable.layer == RenderLayer::Hud { Vec2::ZERO } else { shake };
+        let drawable = DrawableComponent{position: drawable.position + offset, ..*drawable};
+        let outline = match render_mode {
+            RenderMode::Vector => outline.map(|outline| outline.world_points(drawable.position, drawable.rotation)),
+            RenderMode::Sprites => None,
+        };
+        render_queue.sprites.push(RenderData{drawable, outline, color});
+    }
+    if let Some(star_texture_id) = texture_map.mapping.get("star") {
+        queue_stars(render_queue, starfield, camera, *star_texture_id);
+    }
+
+    // Sort everything into the order it should be drawn in, bottom layer first, and then from the lowest z-index up
+    // within each layer
+    render_queue.sprites.sort_by_key(|data| (data.drawable.layer, data.drawable.z_index));
+}
+
+/// Queues up the stars at the very back of the background layer. They cover the whole canvas wherever the camera is, so
+/// they are laid out in canvas pixels, and then placed in the world wherever those pixels are in view. They only shake
+/// as much as they drift
+fn queue_stars(render_queue: &mut RenderQueue, starfield: &Starfield, camera: &GameCamera, star_texture_id: Uuid) {
+    let canvas_center = Canvas::size() / 2.0;
+    for layer in starfield.layers.iter() {
+        for star in layer.stars.iter() {
+            let position = starfield.star_position(layer, star, render_queue.shake);
+            let drawable = DrawableComponent{
+                pivot: Vec2::ZERO,
+                scale: Vec2::splat(star.size / camera.zoom),
+                layer: RenderLayer::Background,
+                z_index: i32::MIN,
+                ..DrawableComponent::new(star_texture_id, camera.target + (position - canvas_center) / camera.zoom, 0.0)
+            };
+            render_queue.sprites.push(RenderData{drawable, outline: None, color: Color::new(1.0, 1.0, 1.0, star.brightness)});
+        }
+    }
+}
+
+#[system]
+pub fn draw_world(#[resource] render_queue: &RenderQueue,
+                  #[resource] texture_store: &TextureStore,
+                  #[resource] camera: &GameCamera,
+                  #[resource] canvas: &Canvas) {
+    // The world, and the effects over it, are drawn through the camera, in world units
+    set_camera(&camera.camera_2d(canvas));
+    draw_sprites(render_queue, RenderLayer::Background, texture_store);
+    draw_sprites(render_queue, RenderLayer::World, texture_store);
+}
+
+#[system]
+#[read_component(LaserBeamComponent)]
+#[read_component(DrawableComponent)]
+#[read_component(HyperspaceComponent)]
+#[read_component(ActiveEffectsComponent)]
+#[read_component(ScorePopupComponent)]
+#[read_component(TimedExistenceComponent)]
+pub fn draw_effects(world: &SubWorld,
+                    #[resource] render_queue: &RenderQueue,
+                    #[resource] texture_store: &TextureStore,
+                    #[resource] particle_pool: &ParticlePool,
+                    #[resource] time_resource: &TimeResource) {
+    let frame_t = time_resource.absolute_time;
+    let shake = render_queue.shake;
+
+    // Effects are drawn over the world, but under the HUD
+    draw_sprites(render_queue, RenderLayer::Effects, texture_store);
+
+    // Particles are just drawn as dots, which change color and shrink as they get older
+    for particle in particle_pool.particles.iter() {
+        let position = particle.position + shake;
+        draw_circle(position.x, position.y, particle.size(frame_t), particle.color(frame_t));
+    }
+
+    // Laser beams have no texture, they are just drawn as a line along their length
+    let mut beam_query = <&LaserBeamComponent>::query();
+    for beam in beam_query.iter(world) {
+        let (start, end) = (beam.start + shake, beam.end + shake);
+        draw_line(start.x, start.y, end.x, end.y, 2.0, RED);
+    }
+
+    // Ships coming out of hyperspace have a ring collapse in on them as they materialize
+    let mut hyperspace_query = <(&DrawableComponent, &HyperspaceComponent)>::query();
+    for (drawable, materializing) in hyperspace_query.iter(world) {
+        let width = drawable.size(texture_store.sprite_size(&drawable.texture_id)).x;
+        let center = drawable.position + shake;
+        let progress = ((frame_t - materializing.started_at) / materializing.duration).clamp(0.0, 1.0) as f32;
+        draw_circle_lines(center.x, center.y, width * 1.5 * (1.0 - progress), 1.0, VIOLET);
+    }
+
+    // Draw a bubble around any ship with its shield up
+    let mut shield_query = <(&DrawableComponent, &ActiveEffectsComponent)>::query();
+    for (drawable, effects) in shield_query.iter(world) {
+        if effects.is_active(PowerUpKind::Shield) {
+            let width = drawable.size(texture_store.sprite_size(&drawable.texture_id)).x;
+            let center = drawable.position + shake;
+            draw_circle_lines(center.x, center.y, width * 0.75, 1.0, SKYBLUE);
+        }
+    }
+
+    // Floating popups show how much each kill was worth, drifting upwards and fading away
+    let mut popup_query = <(&ScorePopupComponent, &TimedExistenceComponent)>::query();
+    for (popup, timed) in popup_query.iter(world) {
+        let age = ((frame_t - timed.created_at) / timed.max_lifetime).clamp(0.0, 1.0) as f32;
+        let popup_text = if popup.multiplier > 1 {
+            format!("+{} x{}", popup.points, popup.multiplier)
+        } else {
+            format!("+{}", popup.points)
+        };
+        let position = popup.position + shake - Vec2::new(measure_text(&popup_text, None, 20, 1.0).width / 2.0, age * SCORE_POPUP_RISE);
+        draw_text_ex(
+            &popup_text,
+            position.x,
+            position.y,
+            TextParams{
+                font_size: 20,
+                color: Color::new(1.0, 1.0, 1.0, 1.0 - age),
+                ..Default::default()
+            });
+    }
+}
+
+#[system]
+#[read_component(PlayerComponent)]
+#[read_component(ShieldComponent)]
+#[read_component(WeaponComponent)]
+#[read_component(ActiveEffectsComponent)]
+pub fn draw_hud(world: &SubWorld,
+                #[resource] render_queue: &RenderQueue,
+                #[resource] texture_store: &TextureStore,
+                #[resource] score_resource: &ScoreResource,
+                #[resource] wave_resource: &WaveResource,
+                #[resource] time_resource: &TimeResource,
+                #[resource] canvas: &Canvas) {
+    let frame_t = time_resource.absolute_time;
+
+    // The HUD is drawn over everything else, straight onto the canvas rather than through the camera
+    set_camera(&canvas.ui_camera());
+    draw_sprites(render_queue, RenderLayer::Hud, texture_store);
+
+    // Draw the players score to the top of the screen, along with their combo multiplier once they have one
+    let score_text = if score_resource.multiplier > 1 {
+        format!("{} x{}", score_resource.score, score_resource.multiplier)
+    } else {
+        score_resource.score.to_string()
+    };
+    draw_centered_text(&score_text, measure_text(&score_text, None, 50, 1.0).height + 5.0, 50, WHITE);
+
+    // Announce the upcoming wave while the field is empty
+    if wave_resource.interstitial_started_at.is_some() {
+        draw_centered_text(&format!("Wave {}", wave_resource.wave), CANVAS_HEIGHT / 2., 50, WHITE);
+    }
+
+    // Draw the ship's shield bar in the top right corner
+    let mut shield_bar_query = <(&PlayerComponent, &ShieldComponent)>::query();
+    for (_, shield) in shield_bar_query.iter(world) {
+        let bar_x = CANVAS_WIDTH - SHIELD_BAR_WIDTH - 10.0;
+        let fill = shield.points as f32 / shield.max_points as f32;
+        draw_rectangle(bar_x, 10.0, SHIELD_BAR_WIDTH * fill, SHIELD_BAR_HEIGHT, SKYBLUE);
+        draw_rectangle_lines(bar_x, 10.0, SHIELD_BAR_WIDTH, SHIELD_BAR_HEIGHT, 2.0, WHITE);
+    }
+
+    // Show which weapon the player currently has equipped in the bottom left corner
+    let mut weapon_query = <(&PlayerComponent, &WeaponComponent)>::query();
+    for (_, weapon) in weapon_query.iter(world) {
+        draw_text_ex(
+            weapon.name,
+            5.0,
+            CANVAS_HEIGHT - 10.0,
+            TextParams{
+                font_size: 20,
+                color: WHITE,
+                ..Default::default()
+            });
+    }
+
+    // List any power ups the player has active underneath their lives, along with how long they have left
+    let mut effects_query = <(&PlayerComponent, &ActiveEffectsComponent)>::query();
+    for (_, effects) in effects_query.iter(world) {
+        for (index, effect) in effects.effects.iter().enumerate() {
+            let effect_text = format!("{} {:.1}", effect.kind.label(), effect.expires_at - frame_t);
+            draw_text_ex(
+                &effect_text,
+                5.0,
+                60.0 + index as f32 * 20.0,
+                TextParams{
+                    font_size: 20,
+                    color: WHITE,
+                    ..Default::default()
+                });
+        }
+    }
+}
+
+/// Draws the player's remaining lives into the HUD, along with the announcement for any score milestone they have just
+/// reached. This runs after the rest of the HUD, so it is already being drawn onto the canvas
+#[system]
+#[read_component(MilestoneReachedComponent)]
+#[read_component(TimedExistenceComponent)]
+pub fn draw_lives(world: &SubWorld,
+                  #[resource] texture_store: &TextureStore,
+                  #[resource] texture_map: &TextureMap,
+                  #[resource] lives_resource: &LivesResource,
+                  #[resource] render_mode: &RenderMode,
+                  #[resource] time_resource: &TimeResource) {
+    let frame_t = time_resource.absolute_time;
+
+    // Celebrate any score milestones that have just been reached, by announcing the extra life, and having it flash in
+    // with the others
+    let mut milestone_query = <(&MilestoneReachedComponent, &TimedExistenceComponent)>::query();
+    let milestone_flash = milestone_query.iter(world).next().map(|(milestone, timed)| {
+        let flash = ((frame_t - timed.created_at) / MILESTONE_FLASH_INTERVAL) as i64 % 2 == 0;
+        draw_centered_text(&format!("{} POINTS - EXTRA LIFE!", milestone.score), 100.0, 30, if flash { GOLD } else { WHITE });
+        flash
+    });
+
+    // Draw a ship in the top left corner for each life the player has left, as an outline if that's how the world is
+    // being drawn
+    let ship_texture = texture_store.get_named(texture_map, "ship");
+    let ship_outline = OutlineComponent::new(&SHIP_OUTLINE);
+    for life in 0..lives_resource.lives {
+        let newest = life == lives_resource.lives - 1;
+        let color = if newest && milestone_flash == Some(true) { GOLD } else { WHITE };
+        let top_left = Vec2::new(5.0 + life as f32 * ship_texture.width(), 5.0);
+        match render_mode {
+            RenderMode::Sprites => {
+                let draw_params = DrawTextureParams{
+                    rotation: -std::f32::consts::FRAC_PI_2,
+                    ..Default::default()
+                };
+                draw_texture_ex(ship_texture, top_left.x, top_left.y, color, draw_params);
+            }
+            RenderMode::Vector => {
+                let center = top_left + ship_texture.size() / 2.0;
+                draw_outline(&ship_outline.world_points(center, -std::f32::consts::FRAC_PI_2), color);
+            }
+        }
+    }
+}
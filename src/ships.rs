@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::Path;
+use crate::components::ShipPhysicsComponent;
+use crate::config::parse_key_values;
+
+/// A kind of ship the player can fly, and how it handles
+#[derive(Clone, Debug)]
+pub struct ShipClass {
+    pub name: String,
+    pub physics: ShipPhysicsComponent,
+}
+
+/// Every ship class that has been loaded, along with the one the player has picked to fly
+pub struct ShipClasses {
+    pub classes: Vec<ShipClass>,
+    pub selected: usize,
+}
+
+impl ShipClasses {
+    pub fn selected(&self) -> &ShipClass {
+        &self.classes[self.selected]
+    }
+
+    /// Picks the class that comes after the current one, wrapping back around to the first
+    pub fn select_next(&mut self) -> &ShipClass {
+        self.selected = (self.selected + 1) % self.classes.len();
+        self.selected()
+    }
+}
+
+/// Loads every ship definition (any file ending in `.ship`) in the given directory, sorted by file name, so the
+/// order ships are offered in doesn't depend on the file system
+pub fn load_ship_classes(directory: impl AsRef<Path>) -> Result<ShipClasses, String> {
+    let directory = directory.as_ref();
+    let entries = fs::read_dir(directory).map_err(|err| format!("{}: {}", directory.display(), err))?;
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|err| format!("{}: {}", directory.display(), err))?.path();
+        if path.extension().is_some_and(|extension| extension == "ship") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut classes = Vec::new();
+    for path in paths {
+        let definition = fs::read_to_string(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        classes.push(parse_ship_definition(&definition).map_err(|err| format!("{}: {}", path.display(), err))?);
+    }
+
+    if classes.is_empty() {
+        return Err(format!("{}: no ship definitions found", directory.display()));
+    }
+    Ok(ShipClasses{classes, selected: 0})
+}
+
+/// Parses a ship definition, written in the same `key = value` format as the settings file. Every key has to be given
+/// exactly once
+pub fn parse_ship_definition(definition: &str) -> Result<ShipClass, String> {
+    let mut values = parse_key_values(definition)?;
+
+    let name = values.remove("name").ok_or("missing `name`")?.to_string();
+    let mut number = |key: &str| -> Result<f32, String> {
+        let value = values.remove(key).ok_or(format!("missing `{}`", key))?;
+        value.parse().map_err(|_| format!("`{}` should be a number, not `{}`", key, value))
+    };
+    let physics = ShipPhysicsComponent{
+        thrust: number("thrust")?,
+        max_speed: number("max_speed")?,
+        angular_acceleration: number("angular_acceleration")?,
+        max_angular_velocity: number("max_angular_velocity")?,
+        drag: number("drag")?,
+        angular_drag: number("angular_drag")?,
+        angular_velocity: 0.0,
+    };
+
+    // Anything left over isn't something a ship knows about, which is most likely a typo
+    if let Some(key) = values.keys().next() {
+        return Err(format!("unknown key `{}`", key));
+    }
+
+    Ok(ShipClass{name, physics})
+}
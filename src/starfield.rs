@@ -0,0 +1,102 @@
+use std::ops::RangeInclusive;
+use macroquad::math::Vec2;
+use rand::Rng;
+use crate::render::Canvas;
+
+/// How one layer of stars looks, and how far away it is
+struct StarLayerStyle {
+    star_count: usize,
+    /// How far the layer moves, as a fraction of how far the camera moves. Further away layers move less
+    parallax: f32,
+    size: RangeInclusive<f32>,
+    brightness: RangeInclusive<f32>,
+}
+
+/// The layers of stars, from the furthest away to the closest
+const STAR_LAYERS: [StarLayerStyle; 3] = [
+    StarLayerStyle{star_count: 120, parallax: 0.05, size: 1.0..=1.0, brightness: 0.2..=0.4},
+    StarLayerStyle{star_count: 60, parallax: 0.15, size: 1.0..=1.5, brightness: 0.4..=0.7},
+    StarLayerStyle{star_count: 25, parallax: 0.3, size: 1.5..=2.0, brightness: 0.7..=1.0},
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Star {
+    /// Where the star is on the canvas, before the layer has been moved
+    pub position: Vec2,
+    pub size: f32,
+    pub brightness: f32,
+}
+
+pub struct StarLayer {
+    pub stars: Vec<Star>,
+    pub parallax: f32,
+}
+
+/// The stars drawn behind everything else. The canvas is tiled with each layer of stars, which drift the opposite way
+/// to the camera as it moves around, with the closer layers drifting faster, to give a sense of depth
+pub struct Starfield {
+    pub layers: Vec<StarLayer>,
+    /// How far the camera has moved, in total, since the starfield was made
+    pub scroll: Vec2,
+    /// Where the camera was looking the last time the starfield followed it
+    last_camera_target: Option<Vec2>,
+}
+
+impl Starfield {
+    /// Scatters stars over each layer at random, so every game gets a sky of its own
+    pub fn generate(rng: &mut impl Rng) -> Self {
+        let canvas_size = Canvas::size();
+        let layers = STAR_LAYERS
+            .iter()
+            .map(|style| {
+                let stars = (0..style.star_count)
+                    .map(|_| Star{
+                        position: Vec2::new(rng.gen_range(0.0..canvas_size.x), rng.gen_range(0.0..canvas_size.y)),
+                        size: rng.gen_range(style.size.clone()),
+                        brightness: rng.gen_range(style.brightness.clone()),
+                    })
+                    .collect();
+                StarLayer{stars, parallax: style.parallax}
+            })
+            .collect();
+        Starfield{layers, scroll: Vec2::ZERO, last_camera_target: None}
+    }
+
+    /// Scrolls the stars by however far the camera has moved since last time. The stars only drift while the view
+    /// itself moves, so they hold still when the camera is fixed, or stopped at the edge of the playfield
+    pub fn follow_camera(&mut self, camera_target: Vec2) {
+        if let Some(last_camera_target) = self.last_camera_target {
+            self.scroll += camera_target - last_camera_target;
+        }
+        self.last_camera_target = Some(camera_target);
+    }
+
+    /// Where a star in the given layer is on the canvas right now, with the screen shaken by `shake`. Stars that drift
+    /// off one edge come back in on the opposite one
+    pub fn star_position(&self, layer: &StarLayer, star: &Star, shake: Vec2) -> Vec2 {
+        let position = star.position + (shake - self.scroll) * layer.parallax;
+        let canvas_size = Canvas::size();
+        Vec2::new(position.x.rem_euclid(canvas_size.x), position.y.rem_euclid(canvas_size.y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn starfield_only_scrolls_while_the_camera_moves() {
+        let mut starfield = Starfield::generate(&mut StdRng::seed_from_u64(0));
+        starfield.follow_camera(Vec2::new(320.0, 240.0));
+        assert_eq!(starfield.scroll, Vec2::ZERO);
+
+        // Like when the camera is fixed, or stopped at the edge of the playfield while the ship keeps going
+        starfield.follow_camera(Vec2::new(320.0, 240.0));
+        assert_eq!(starfield.scroll, Vec2::ZERO);
+
+        starfield.follow_camera(Vec2::new(330.0, 235.0));
+        assert_eq!(starfield.scroll, Vec2::new(10.0, -5.0));
+    }
+}
@@ -1,52 +1,142 @@
-use legion::{Entity, IntoQuery, Query, system};
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::{PI, TAU};
+use std::ops::RangeInclusive;
+use legion::{component, Entity, IntoQuery, Query, system};
 use legion::systems::CommandBuffer;
 use legion::world::SubWorld;
 use macroquad::math::{Rect, Vec2};
 use rand::Rng;
-use crate::components::{AsteroidComponent, BulletComponent, CollisionComponent, DrawableComponent, PlayerComponent, ScoreComponent, TimedExistenceComponent, VelocityComponent};
-use crate::{GameOverResource, ScoreResource, ScreenDimensions, TextureMap, TimeResource};
+use rand::seq::SliceRandom;
+use crate::camera::{CameraEffects, LARGE_ASTEROID_KICK, PLAYER_DEATH_KICK, SAUCER_KICK};
+use crate::collision::{polygons_overlap, rect_outline, segment_hits_polygon, swept_rect_overlaps};
+use crate::animation::{SHIP_IDLE, SHIP_THRUST};
+use crate::particles::{ParticlePool, ASTEROID_DEBRIS, BULLET_IMPACT, SHIP_EXPLOSION};
+use crate::ships::ShipClasses;
+use crate::power_ups::{ALL_POWER_UPS, POWER_UP_DROP_CHANCE, RAPID_FIRE_COOLDOWN_MULTIPLIER, SPREAD_SHOT_ANGLE};
+use crate::components::{ActiveEffectsComponent, AnimationComponent, AsteroidComponent, AsteroidSize, BulletComponent, CameraKickComponent, CollisionComponent, DamageFlashComponent, DrawableComponent, EnemyBulletComponent, FastMoverComponent, HealthComponent, HomingComponent, HyperspaceComponent, InvulnerableComponent, LaserBeamComponent, OutlineComponent, ParticleBurstComponent, ParticleEmitterComponent, PlayerComponent, PowerUpComponent, PowerUpKind, ProjectilePattern, SaucerComponent, ScoreComponent, ShieldComponent, ShipPhysicsComponent, TimedExistenceComponent, VelocityComponent, WeaponComponent, WrapBehavior};
+use crate::{entities, DifficultyCurve, GameOverResource, GameRules, LivesResource, PlayfieldDimensions, RespawnResource, SaucerSpawnResource, ScoreMilestones, ScoreResource, TextureMap, TimeResource, WaveResource, WAVE_INTERSTITIAL_DURATION};
+
+/// How long, in seconds, to wait after the player's ship is destroyed before trying to spawn a new one
+const RESPAWN_DELAY: f64 = 2.0;
+
+/// Asteroids closer than this to the center of the playfield will prevent the player from respawning
+const SAFE_SPAWN_RADIUS: f32 = 100.0;
+
+/// How much damage flying into an asteroid or saucer does to the player's ship
+const COLLISION_DAMAGE: i32 = 1;
+
+/// How long, in seconds, the player's ship is invulnerable for after taking a hit that it survives
+const HIT_INVULNERABILITY: f64 = 1.0;
+
+/// How long, in seconds, entities are tinted for after taking damage
+const DAMAGE_FLASH_DURATION: f64 = 0.1;
+
+/// The chance that a hyperspace jump goes wrong, and destroys the ship
+const HYPERSPACE_MALFUNCTION_CHANCE: f64 = 0.05;
+
+/// How long, in seconds, a ship takes to materialize after a hyperspace jump
+const HYPERSPACE_MATERIALIZE_DURATION: f64 = 0.75;
+
+/// How fast saucers fly across the screen, and how fast they can drift up or down while doing so
+const SAUCER_SPEED: f32 = 1.5;
+const SAUCER_DRIFT_SPEED: f32 = 1.0;
+
+/// How long, in seconds, a saucer keeps to its current course before picking a new one
+const SAUCER_COURSE_DURATION: RangeInclusive<f64> = 1.0..=2.5;
+
+/// How long, in seconds, to wait between saucers
+const SAUCER_SPAWN_INTERVAL: RangeInclusive<f64> = 10.0..=20.0;
+
+/// How far off target (in radians, either way) a saucer's shots can be. Saucers start out with poor aim, which gets
+/// steadily better as the player's score climbs, until it stops improving at `SAUCER_BEST_AIM_SCORE`
+const SAUCER_WORST_AIM: f32 = 0.6;
+const SAUCER_BEST_AIM: f32 = 0.05;
+const SAUCER_BEST_AIM_SCORE: f32 = 10000.0;
 
 #[system(for_each)]
-pub fn apply_velocity(velocity: &mut VelocityComponent,
+#[allow(clippy::too_many_arguments)]
+pub fn apply_velocity(entity: &Entity,
+                      velocity: &mut VelocityComponent,
                       drawable: &mut DrawableComponent,
                       collide: Option<&mut CollisionComponent>,
-                      player: Option<&PlayerComponent>,
-                      bullet: Option<&BulletComponent>,
-                      #[resource] screen_dimensions: &ScreenDimensions) {
+                      wrap: Option<&WrapBehavior>,
+                      fast_mover: Option<&mut FastMoverComponent>,
+                      cmd: &mut CommandBuffer,
+                      #[resource] playfield: &PlayfieldDimensions) {
     drawable.position += velocity.velocity;
 
-    // Wrap the screen, except when we're working with a bullet, they should not wrap
-    if bullet.is_none() {
-        if drawable.position.x > screen_dimensions.width {
-            drawable.position.x = 0.0;
-        }
-
-        if drawable.position.x < 0.0 {
-            drawable.position.x = screen_dimensions.width
-        }
+    // Deal with anything that has gone off the edge of the playfield, depending on how it is meant to behave there
+    let (width, height) = (playfield.width, playfield.height);
+    let off_screen = drawable.position.x < 0.0 || drawable.position.x > width || drawable.position.y < 0.0 || drawable.position.y > height;
+    let mut teleported = false;
+    if off_screen {
+        match wrap.copied().unwrap_or(WrapBehavior::Wrap) {
+            WrapBehavior::Wrap => {
+                if drawable.position.x > width {
+                    drawable.position.x = 0.0;
+                }
 
-        if drawable.position.y > screen_dimensions.height {
-            drawable.position.y = 0.0;
-        }
+                if drawable.position.x < 0.0 {
+                    drawable.position.x = width
+                }
 
-        if drawable.position.y < 0.0 {
-            drawable.position.y = screen_dimensions.height
-        }
+                if drawable.position.y > height {
+                    drawable.position.y = 0.0;
+                }
 
-        if let Some(_) = player {
-            // If this entity is the player, apply a "braking force" on its velocity, so it slows down
-            // over time, and cannot accelerate infinitely
-            velocity.velocity *= 0.98;
+                if drawable.position.y < 0.0 {
+                    drawable.position.y = height
+                }
+                teleported = true;
+            }
+            WrapBehavior::Clamp => {
+                if drawable.position.x < 0.0 || drawable.position.x > width {
+                    velocity.velocity.x = 0.0;
+                }
+                if drawable.position.y < 0.0 || drawable.position.y > height {
+                    velocity.velocity.y = 0.0;
+                }
+                drawable.position = drawable.position.clamp(Vec2::ZERO, Vec2::new(width, height));
+            }
+            WrapBehavior::Bounce => {
+                if drawable.position.x < 0.0 || drawable.position.x > width {
+                    velocity.velocity.x = -velocity.velocity.x;
+                }
+                if drawable.position.y < 0.0 || drawable.position.y > height {
+                    velocity.velocity.y = -velocity.velocity.y;
+                }
+                drawable.position = drawable.position.clamp(Vec2::ZERO, Vec2::new(width, height));
+            }
+            WrapBehavior::DespawnOffscreen => cmd.remove(*entity),
         }
     }
 
-    // If this entity has a collision component, update the rectangle that surrounds the entity to match the current
+    // If this entity has a collision component, keep the rectangle that surrounds the entity centered on the current
     // position of the entity - We know it has a position, because it has a drawable component
     if let Some(collision) = collide {
-        collision.rect.move_to(drawable.position);
+        let previous_position = collision.rect.point();
+        collision.rect.move_to(drawable.position - collision.rect.size() / 2.0);
+
+        // Remember where fast movers started this frame, so their whole path can be checked for collisions. If they
+        // have just wrapped around, they didn't really cross the whole playfield, so there is no path to check
+        if let Some(fast_mover) = fast_mover {
+            fast_mover.previous_position = if teleported { collision.rect.point() } else { previous_position };
+        }
     }
 }
 
+#[system(for_each)]
+pub fn apply_ship_physics(physics: &mut ShipPhysicsComponent, velocity: &mut VelocityComponent, drawable: &mut DrawableComponent) {
+    // Turn the ship by however fast it is spinning, and then let drag slow both the spin and the ship itself down,
+    // so it drifts to a stop when the player lets go of the controls
+    drawable.rotation += physics.angular_velocity;
+    physics.angular_velocity *= physics.angular_drag;
+    velocity.velocity *= physics.drag;
+
+    // Thrust can only push the ship so fast
+    velocity.velocity = velocity.velocity.clamp_length_max(physics.max_speed);
+}
+
 #[system]
 #[write_component(DrawableComponent)]
 #[read_component(AsteroidComponent)]
@@ -71,67 +161,832 @@ pub fn destroy_timed_entities(objects: &mut Query<(Entity, &TimedExistenceCompon
     }
 }
 
+/// Checks whether a collider has hit the target rect this frame. Fast movers are checked along the full path they took
+/// this frame, so they can't tunnel through anything small, everything else only needs to check where it is right now
+fn collides_with(collision: &CollisionComponent, fast_mover: Option<&FastMoverComponent>, target: &Rect) -> bool {
+    match fast_mover {
+        Some(fast_mover) => swept_rect_overlaps(&collision.rect, fast_mover.previous_position, collision.rect.point(), target),
+        None => collision.rect.overlaps(target),
+    }
+}
+
+/// Something the player or an enemy fired that could hit an asteroid or saucer this frame, either a bullet or a laser
+/// beam. Every shot is treated as a rect that travelled from `from` to `to` during the frame, for bullets that aren't
+/// fast movers these are the same point
+struct Shot {
+    entity: Entity,
+    rect: Rect,
+    from: Vec2,
+    to: Vec2,
+    damage: i32,
+    fired_by_player: bool,
+    piercing: bool,
+}
+
+impl Shot {
+    /// Checks whether the shot hit the target this frame. Targets with an outline are only hit if the shot's path
+    /// actually crosses into the outline, rather than just clipping the corner of their rect. The outline is only
+    /// placed in the world once the cheaper rect check has passed
+    fn hits(&self, target: &Rect, target_outline: Option<&OutlineComponent>, target_drawable: &DrawableComponent) -> bool {
+        if !swept_rect_overlaps(&self.rect, self.from, self.to, target) {
+            return false;
+        }
+        let half_size = self.rect.size() / 2.0;
+        target_outline.is_none_or(|outline| {
+            let outline = outline.world_points(target_drawable.position, target_drawable.rotation);
+            segment_hits_polygon(self.from + half_size, self.to + half_size, &outline)
+        })
+    }
+
+    /// Throws up a spray of sparks where the shot hit the target, back the way the shot came from. Beams don't have a
+    /// single point they end at, so their sparks come from the middle of whatever they hit
+    fn impact(&self, cmd: &mut CommandBuffer, target: &Rect) {
+        let position = if self.piercing { target.center() } else { self.to + self.rect.size() / 2.0 };
+        let back = self.from - self.to;
+        cmd.push(entities::particle_burst(&BULLET_IMPACT, position, back.y.atan2(back.x)));
+    }
+}
+
 #[system]
 #[read_component(BulletComponent)]
+#[read_component(EnemyBulletComponent)]
+#[write_component(LaserBeamComponent)]
+#[read_component(FastMoverComponent)]
 #[read_component(AsteroidComponent)]
+#[read_component(SaucerComponent)]
 #[read_component(CollisionComponent)]
 #[read_component(DrawableComponent)]
+#[read_component(OutlineComponent)]
 #[read_component(ScoreComponent)]
-pub fn handle_bullet_collisions(cmd: &mut CommandBuffer, world: &mut SubWorld, #[resource] texture_map: &TextureMap, #[resource] score_resource: &mut ScoreResource) {
-    // Iterate through every bullet, and then every asteroid, to see if there are any collisions
-    // This is inefficient, but for such a small game, is just fine
-    let (mut bullet_world, mut asteroid_world) = world.split::<(&BulletComponent, &CollisionComponent)>();
-    let mut bullet_query = <(Entity, &BulletComponent, &CollisionComponent)>::query();
-    for (bullet_entity, _, bullet_collision) in bullet_query.iter_mut(&mut bullet_world) {
-        let mut asteroid_query = <(Entity, &CollisionComponent, &AsteroidComponent, &DrawableComponent, &ScoreComponent)>::query();
-        for (asteroid_entity, asteroid_collision, asteroid, asteroid_drawable, score) in asteroid_query.iter_mut(&mut asteroid_world) {
-            if bullet_collision.rect.overlaps(&asteroid_collision.rect) {
-                // If this entity overlaps with the current entity, add both for removal
-                cmd.remove(*bullet_entity);
+#[write_component(HealthComponent)]
+pub fn handle_bullet_collisions(cmd: &mut CommandBuffer,
+                                world: &mut SubWorld,
+                                #[resource] texture_map: &TextureMap,
+                                #[resource] score_resource: &mut ScoreResource,
+                                #[resource] wave_resource: &WaveResource,
+                                #[resource] difficulty_curve: &DifficultyCurve,
+                                #[resource] time_resource: &TimeResource) {
+    // Gather up everything that has been fired, bullets are used up by the first thing they hit, but laser beams keep
+    // on going through everything in their path
+    let mut shots = Vec::new();
+    let mut bullet_query = <(Entity, &CollisionComponent, Option<&FastMoverComponent>, Option<&BulletComponent>, Option<&EnemyBulletComponent>)>::query()
+        .filter(component::<BulletComponent>() | component::<EnemyBulletComponent>());
+    for (entity, collision, fast_mover, player_bullet, enemy_bullet) in bullet_query.iter(world) {
+        shots.push(Shot{
+            entity: *entity,
+            rect: collision.rect,
+            from: fast_mover.map_or(collision.rect.point(), |fast_mover| fast_mover.previous_position),
+            to: collision.rect.point(),
+            damage: player_bullet.map(|bullet| bullet.damage).or(enemy_bullet.map(|bullet| bullet.damage)).unwrap_or(0),
+            fired_by_player: player_bullet.is_some(),
+            piercing: false,
+        });
+    }
+    let mut beam_query = <(Entity, &mut LaserBeamComponent)>::query();
+    for (entity, beam) in beam_query.iter_mut(world) {
+        if beam.damage_dealt {
+            continue;
+        }
+        shots.push(Shot{
+            entity: *entity,
+            rect: Rect::new(beam.start.x, beam.start.y, 0.0, 0.0),
+            from: beam.start,
+            to: beam.end,
+            damage: beam.damage,
+            fired_by_player: true,
+            piercing: true,
+        });
+        beam.damage_dealt = true;
+    }
+
+    // Iterate through every shot, and then every asteroid and saucer, to see if there are any collisions
+    // This is inefficient, but for such a small game, is just fine. Keep track of what has already been destroyed, so
+    // nothing can be destroyed twice if it is hit by more than one shot in the same frame
+    let mut destroyed = HashSet::new();
+    let mut rng = rand::thread_rng();
+    for shot in shots.iter() {
+        let mut shot_spent = false;
+        let mut asteroid_query = <(Entity, &CollisionComponent, &AsteroidComponent, &DrawableComponent, Option<&OutlineComponent>, &ScoreComponent, &mut HealthComponent)>::query();
+        for (asteroid_entity, asteroid_collision, asteroid, asteroid_drawable, asteroid_outline, score, health) in asteroid_query.iter_mut(world) {
+            if destroyed.contains(asteroid_entity) || !shot.hits(&asteroid_collision.rect, asteroid_outline, asteroid_drawable) {
+                continue;
+            }
+
+            // Bigger asteroids can take a few hits before they break up, flash them so it's clear the hit landed
+            shot.impact(cmd, &asteroid_collision.rect);
+            health.hit_points -= shot.damage;
+            if health.hit_points > 0 {
+                cmd.add_component(*asteroid_entity, DamageFlashComponent{started_at: time_resource.absolute_time});
+            } else {
+                destroyed.insert(*asteroid_entity);
                 cmd.remove(*asteroid_entity);
+                cmd.push(entities::particle_burst(&ASTEROID_DEBRIS, asteroid_collision.rect.center(), 0.0));
+                if asteroid.size == AsteroidSize::Large {
+                    cmd.push((LARGE_ASTEROID_KICK,));
+                }
+
+                // Update the score based on the size of the asteroid, enemies don't score any points for this though.
+                // Every so often, the player is also rewarded with a power up
+                if shot.fired_by_player {
+                    let multiplier = score_resource.add_kill(score.value, time_resource.absolute_time);
+                    cmd.push(entities::score_popup(score.value, multiplier, asteroid_collision.rect.center(), time_resource.absolute_time));
+
+                    if rng.gen_bool(POWER_UP_DROP_CHANCE) {
+                        let kind = *ALL_POWER_UPS.choose(&mut rng).unwrap();
+                        if let Some(tex_uuid) = texture_map.mapping.get(kind.texture_name()) {
+                            cmd.push(entities::power_up(&mut rng, kind, asteroid_drawable.position, *tex_uuid, time_resource.absolute_time));
+                        }
+                    }
+                }
 
-                // Update the score based on the size of the asteroid
-                score_resource.score += score.value;
-
-                if asteroid.is_large {
-                    // If this was a large asteroid, spawn a random number of smaller asteroids
-                    let mut rng = rand::thread_rng();
-                    for _ in 2..rng.gen_range(3..=10) {
-                        let rotation = rng.gen_range(-10.0..=10.0);
-                        let pos = asteroid_drawable.position;
-                        if let Some(tex_uuid) = texture_map.mapping.get("small_asteroid") {
-                            cmd.push(
-                                (
-                                    AsteroidComponent{is_large: false},
-                                    DrawableComponent{texture_id: *tex_uuid, position: pos, rotation: rotation},
-                                    VelocityComponent{velocity: Vec2::from_angle(rotation) * rng.gen_range(0.1..=1.0)},
-                                    CollisionComponent{rect: Rect::new(pos[0], pos[1], 16., 16.), collided: false},
-                                    ScoreComponent{value: 10},
-                                )
-                            );
+                // Break the asteroid up into smaller fragments, if it is big enough to do so
+                let tier = asteroid.size.tier();
+                if let Some(fragment_size) = tier.splits_into {
+                    let speed_multiplier = difficulty_curve.speed_multiplier(wave_resource.wave);
+                    for _ in 0..rng.gen_range(tier.fragment_count.clone()) {
+                        if let Some(fragment) = entities::asteroid(&mut rng, fragment_size, asteroid_drawable.position, speed_multiplier, texture_map) {
+                            cmd.push(fragment);
                         }
                     }
                 }
             }
+
+            // A bullet has been used up, so it can't go on to hit anything else
+            if !shot.piercing {
+                cmd.remove(shot.entity);
+                shot_spent = true;
+                break;
+            }
+        }
+
+        // Only the player's shots can bring down saucers
+        if shot_spent || !shot.fired_by_player {
+            continue;
+        }
+
+        let mut saucer_query = <(Entity, &CollisionComponent, &SaucerComponent, &DrawableComponent, Option<&OutlineComponent>, &ScoreComponent, &mut HealthComponent)>::query();
+        for (saucer_entity, saucer_collision, _, saucer_drawable, saucer_outline, score, health) in saucer_query.iter_mut(world) {
+            if destroyed.contains(saucer_entity) || !shot.hits(&saucer_collision.rect, saucer_outline, saucer_drawable) {
+                continue;
+            }
+
+            shot.impact(cmd, &saucer_collision.rect);
+            health.hit_points -= shot.damage;
+            if health.hit_points > 0 {
+                cmd.add_component(*saucer_entity, DamageFlashComponent{started_at: time_resource.absolute_time});
+            } else {
+                destroyed.insert(*saucer_entity);
+                cmd.remove(*saucer_entity);
+                cmd.push(entities::particle_burst(&SHIP_EXPLOSION, saucer_collision.rect.center(), 0.0));
+                if let Some(explosion_texture_id) = texture_map.mapping.get("explosion") {
+                    cmd.push(entities::explosion(saucer_collision.rect.center(), *explosion_texture_id, time_resource.absolute_time));
+                }
+                cmd.push((SAUCER_KICK,));
+                let multiplier = score_resource.add_kill(score.value, time_resource.absolute_time);
+                cmd.push(entities::score_popup(score.value, multiplier, saucer_collision.rect.center(), time_resource.absolute_time));
+            }
+
+            if !shot.piercing {
+                cmd.remove(shot.entity);
+                break;
+            }
+        }
+    }
+}
+
+/// Something that can hurt the player if they fly into it, and how much it hurts
+struct Hazard {
+    entity: Entity,
+    rect: Rect,
+    outline: Option<Vec<Vec2>>,
+    fast_mover: Option<FastMoverComponent>,
+    damage: i32,
+    survives_impact: bool,
+}
+
+impl Hazard {
+    /// Checks whether the hazard has hit the player this frame. If the player has an outline, the hazard has to
+    /// actually touch it, rather than just the player's rect. Fast movers are checked along the path they took this
+    /// frame, anything else by its own outline, or its rect if it doesn't have one
+    fn hits(&self, player_rect: &Rect, player_outline: Option<&[Vec2]>) -> bool {
+        let collision = CollisionComponent{rect: self.rect, collided: false};
+        if !collides_with(&collision, self.fast_mover.as_ref(), player_rect) {
+            return false;
+        }
+        let Some(player_outline) = player_outline else {
+            return true;
+        };
+
+        match (&self.fast_mover, &self.outline) {
+            (Some(fast_mover), _) => {
+                let half_size = self.rect.size() / 2.0;
+                segment_hits_polygon(fast_mover.previous_position + half_size, self.rect.point() + half_size, player_outline)
+            }
+            (None, Some(outline)) => polygons_overlap(outline, player_outline),
+            (None, None) => polygons_overlap(&rect_outline(&self.rect), player_outline),
         }
     }
 }
 
 #[system]
 #[read_component(PlayerComponent)]
+#[read_component(InvulnerableComponent)]
+#[read_component(ActiveEffectsComponent)]
 #[read_component(AsteroidComponent)]
+#[read_component(SaucerComponent)]
+#[read_component(EnemyBulletComponent)]
+#[read_component(FastMoverComponent)]
 #[read_component(CollisionComponent)]
-pub fn handle_player_collision(world: &mut SubWorld, #[resource] game_over_resource: &mut GameOverResource) {
-    // Check each astroid against the player, to see if there are any collisions
+#[read_component(DrawableComponent)]
+#[read_component(OutlineComponent)]
+#[read_component(HyperspaceComponent)]
+#[write_component(ShieldComponent)]
+#[write_component(HealthComponent)]
+pub fn handle_player_collision(cmd: &mut CommandBuffer,
+                               world: &mut SubWorld,
+                               #[resource] lives_resource: &mut LivesResource,
+                               #[resource] respawn_resource: &mut RespawnResource,
+                               #[resource] game_over_resource: &mut GameOverResource,
+                               #[resource] texture_map: &TextureMap,
+                               #[resource] time_resource: &TimeResource) {
+    // Gather up every hazard (asteroids, saucers, and their bullets) that could hit the player. Bullets do their own
+    // damage, anything else just does the damage of a collision
+    let mut hazard_query = <(Entity, &CollisionComponent, &DrawableComponent, Option<&OutlineComponent>, Option<&FastMoverComponent>, Option<&EnemyBulletComponent>, Option<&AsteroidComponent>)>::query()
+        .filter(component::<AsteroidComponent>() | component::<SaucerComponent>() | component::<EnemyBulletComponent>());
+    let hazards: Vec<Hazard> = hazard_query.iter(world)
+        .map(|(entity, collision, drawable, outline, fast_mover, enemy_bullet, asteroid)| Hazard{
+            entity: *entity,
+            rect: collision.rect,
+            outline: outline.map(|outline| outline.world_points(drawable.position, drawable.rotation)),
+            fast_mover: fast_mover.copied(),
+            damage: enemy_bullet.map_or(COLLISION_DAMAGE, |bullet| bullet.damage),
+            survives_impact: asteroid.is_some(),
+        })
+        .collect();
+
+    // Check each hazard against the player, to see if there are any collisions
     // Again, ineffecient, but this is a small game, and it shouldn't matter
-    let (mut player_world, mut asteroid_world) = world.split::<(&PlayerComponent, &CollisionComponent)>();
-    let mut player_query = <(&PlayerComponent, &CollisionComponent)>::query();
-    for (_, player_collision) in player_query.iter_mut(&mut player_world) {
-        let mut asteroid_query = <(&CollisionComponent, &AsteroidComponent)>::query();
-        for (asteroid_collision, asteroid) in asteroid_query.iter_mut(&mut asteroid_world) {
-            if player_collision.rect.overlaps(&asteroid_collision.rect) {
-                game_over_resource.game_over = true;
+    let frame_t = time_resource.absolute_time;
+    let mut outline_query = <(Entity, &DrawableComponent, &OutlineComponent)>::query()
+        .filter(component::<PlayerComponent>());
+    let player_outlines: HashMap<Entity, Vec<Vec2>> = outline_query.iter(world)
+        .map(|(entity, drawable, outline)| (*entity, outline.world_points(drawable.position, drawable.rotation)))
+        .collect();
+    let mut player_query = <(Entity, &PlayerComponent, &CollisionComponent, &ActiveEffectsComponent, &mut HealthComponent, Option<&mut ShieldComponent>, Option<&HyperspaceComponent>)>::query()
+        .filter(!component::<InvulnerableComponent>());
+    for (player_entity, _, player_collision, effects, health, shield, materializing) in player_query.iter_mut(world) {
+        // Nothing can get through while the shield power up is active
+        if effects.is_active(PowerUpKind::Shield) {
+            continue;
+        }
+
+        let player_outline = player_outlines.get(player_entity).map(Vec::as_slice);
+        let hazard = hazards.iter().find(|hazard| hazard.hits(&player_collision.rect, player_outline));
+        let Some(hazard) = hazard else {
+            continue;
+        };
+
+        // Saucers and bullets are destroyed along with whatever they hit, asteroids just keep on going
+        if !hazard.survives_impact {
+            cmd.remove(hazard.entity);
+        }
+
+        // The ship's shield takes the brunt of the damage, and only what gets through it hurts the ship itself. Ships
+        // that are still materializing after a hyperspace jump don't have their shield up yet
+        let damage = match shield {
+            Some(shield) if materializing.is_none() => shield.absorb(hazard.damage, frame_t),
+            _ => hazard.damage,
+        };
+        health.hit_points -= damage;
+
+        if health.hit_points > 0 {
+            // The ship survived, give the player a moment to get clear of whatever hit them
+            cmd.add_component(*player_entity, DamageFlashComponent{started_at: frame_t});
+            cmd.add_component(*player_entity, InvulnerableComponent{started_at: frame_t, duration: HIT_INVULNERABILITY});
+            continue;
+        }
+
+        destroy_player(cmd, *player_entity, player_collision.rect.center(), lives_resource, respawn_resource, game_over_resource, texture_map, frame_t);
+    }
+}
+
+/// Destroys the player's ship, costing them a life. Once they are all gone, the game is over, otherwise a new ship will
+/// be spawned as soon as it is safe to do so
+#[allow(clippy::too_many_arguments)]
+fn destroy_player(cmd: &mut CommandBuffer,
+                  player_entity: Entity,
+                  position: Vec2,
+                  lives_resource: &mut LivesResource,
+                  respawn_resource: &mut RespawnResource,
+                  game_over_resource: &mut GameOverResource,
+                  texture_map: &TextureMap,
+                  time: f64) {
+    // The ship isn't actually removed until the end of the frame, so something else can still run into it, or it can
+    // misjump, after it has already been destroyed. It only costs the player one life however it happens
+    if respawn_resource.died_at == time {
+        return;
+    }
+    respawn_resource.died_at = time;
+
+    cmd.remove(player_entity);
+    cmd.push(entities::particle_burst(&SHIP_EXPLOSION, position, 0.0));
+    if let Some(explosion_texture_id) = texture_map.mapping.get("explosion") {
+        cmd.push(entities::explosion(position, *explosion_texture_id, time));
+    }
+    cmd.push((PLAYER_DEATH_KICK,));
+    lives_resource.lives -= 1;
+    if lives_resource.lives <= 0 {
+        game_over_resource.game_over = true;
+    } else {
+        respawn_resource.respawn_pending = true;
+    }
+}
+
+#[system]
+#[read_component(AsteroidComponent)]
+#[read_component(CollisionComponent)]
+pub fn respawn_player(cmd: &mut CommandBuffer,
+                      world: &mut SubWorld,
+                      #[resource] respawn_resource: &mut RespawnResource,
+                      #[resource] playfield: &PlayfieldDimensions,
+                      #[resource] texture_map: &TextureMap,
+                      #[resource] ship_classes: &ShipClasses,
+                      #[resource] time_resource: &TimeResource) {
+    if !respawn_resource.respawn_pending || time_resource.absolute_time - respawn_resource.died_at < RESPAWN_DELAY {
+        return;
+    }
+
+    // Hold off on spawning the new ship until there are no asteroids near the center of the playfield, so the player
+    // isn't dropped right into the path of something
+    let spawn_position = Vec2::new(playfield.width / 2., playfield.height / 2.);
+    let mut asteroid_query = <(&AsteroidComponent, &CollisionComponent)>::query();
+    let spawn_blocked = asteroid_query.iter(world).any(|(_, collision)| {
+        collision.rect.center().distance(spawn_position) < SAFE_SPAWN_RADIUS
+    });
+    if spawn_blocked {
+        return;
+    }
+
+    if let Some(ship_texture_id) = texture_map.mapping.get("ship_sheet") {
+        entities::spawn_player(cmd, spawn_position, *ship_texture_id, ship_classes.selected().physics, time_resource.absolute_time);
+        respawn_resource.respawn_pending = false;
+    }
+}
+
+#[system]
+pub fn expire_invulnerability(objects: &mut Query<(Entity, &InvulnerableComponent)>, cmd: &mut CommandBuffer, world: &mut SubWorld, #[resource] time_resource: &TimeResource) {
+    for (entity, invulnerable) in objects.iter(world) {
+        if invulnerable.started_at + invulnerable.duration < time_resource.absolute_time {
+            cmd.remove_component::<InvulnerableComponent>(*entity);
+        }
+    }
+}
+
+#[system]
+#[read_component(AsteroidComponent)]
+#[read_component(PlayerComponent)]
+#[read_component(DrawableComponent)]
+pub fn wave_progression(cmd: &mut CommandBuffer,
+                        world: &mut SubWorld,
+                        #[resource] wave_resource: &mut WaveResource,
+                        #[resource] difficulty_curve: &DifficultyCurve,
+                        #[resource] texture_map: &TextureMap,
+                        #[resource] playfield: &PlayfieldDimensions,
+                        #[resource] time_resource: &TimeResource) {
+    match wave_resource.interstitial_started_at {
+        None => {
+            // Once every asteroid in the field has been destroyed, move on to the next wave, giving the player a
+            // short break before it arrives
+            let mut asteroid_query = <&AsteroidComponent>::query();
+            if asteroid_query.iter(world).next().is_none() {
+                wave_resource.wave += 1;
+                wave_resource.interstitial_started_at = Some(time_resource.absolute_time);
+            }
+        }
+        Some(started_at) => {
+            if time_resource.absolute_time - started_at >= WAVE_INTERSTITIAL_DURATION {
+                let mut player_query = <(&PlayerComponent, &DrawableComponent)>::query();
+                let player_position = player_query.iter(world).map(|(_, drawable)| drawable.position).next();
+
+                let mut rng = rand::thread_rng();
+                cmd.extend(entities::asteroid_wave(&mut rng, wave_resource.wave, difficulty_curve, playfield, texture_map, player_position));
+                wave_resource.interstitial_started_at = None;
+            }
+        }
+    }
+}
+
+#[system]
+#[read_component(SaucerComponent)]
+pub fn spawn_saucers(cmd: &mut CommandBuffer,
+                     world: &mut SubWorld,
+                     #[resource] saucer_spawn_resource: &mut SaucerSpawnResource,
+                     #[resource] playfield: &PlayfieldDimensions,
+                     #[resource] texture_map: &TextureMap,
+                     #[resource] time_resource: &TimeResource) {
+    // Only one saucer is allowed on screen at a time
+    let mut saucer_query = <&SaucerComponent>::query();
+    if saucer_query.iter(world).next().is_some() || time_resource.absolute_time < saucer_spawn_resource.next_spawn_at {
+        return;
+    }
+
+    // Saucers enter from either the left or right edge of the screen, and fly towards the opposite one
+    let mut rng = rand::thread_rng();
+    let y = rng.gen_range(playfield.height * 0.1..=playfield.height * 0.9);
+    let (position, velocity) = if rng.gen_bool(0.5) {
+        (Vec2::new(0.0, y), Vec2::new(SAUCER_SPEED, 0.0))
+    } else {
+        (Vec2::new(playfield.width, y), Vec2::new(-SAUCER_SPEED, 0.0))
+    };
+
+    if let Some(saucer_texture_id) = texture_map.mapping.get("saucer") {
+        cmd.push(entities::saucer(position, velocity, *saucer_texture_id, time_resource.absolute_time));
+    }
+    saucer_spawn_resource.next_spawn_at = time_resource.absolute_time + rng.gen_range(SAUCER_SPAWN_INTERVAL);
+}
+
+#[system]
+#[write_component(SaucerComponent)]
+#[write_component(VelocityComponent)]
+#[read_component(PlayerComponent)]
+#[read_component(DrawableComponent)]
+pub fn saucer_ai(cmd: &mut CommandBuffer,
+                 world: &mut SubWorld,
+                 #[resource] rules: &GameRules,
+                 #[resource] score_resource: &ScoreResource,
+                 #[resource] playfield: &PlayfieldDimensions,
+                 #[resource] texture_map: &TextureMap,
+                 #[resource] time_resource: &TimeResource) {
+    let mut player_query = <(&PlayerComponent, &DrawableComponent)>::query();
+    let player_position = player_query.iter(world).map(|(_, drawable)| drawable.position).next();
+
+    // The better the player is doing, the more accurately saucers shoot at them
+    let skill = (score_resource.score as f32 / SAUCER_BEST_AIM_SCORE).clamp(0.0, 1.0);
+    let aim_error = SAUCER_WORST_AIM + (SAUCER_BEST_AIM - SAUCER_WORST_AIM) * skill;
+
+    let mut rng = rand::thread_rng();
+    let frame_t = time_resource.absolute_time;
+    let mut saucer_query = <(Entity, &mut SaucerComponent, &mut VelocityComponent, &DrawableComponent)>::query();
+    for (entity, saucer, velocity, drawable) in saucer_query.iter_mut(world) {
+        // Once the saucer has made it all the way across the screen, it leaves
+        saucer.distance_travelled += velocity.velocity.x.abs();
+        if saucer.distance_travelled >= playfield.width {
+            cmd.remove(*entity);
+            continue;
+        }
+
+        // Every so often, wander up or down (or hold steady) while strafing across the screen
+        if frame_t >= saucer.next_course_change {
+            velocity.velocity.y = SAUCER_DRIFT_SPEED * rng.gen_range(-1..=1) as f32;
+            saucer.next_course_change = frame_t + rng.gen_range(SAUCER_COURSE_DURATION);
+        }
+
+        // Take a shot at the player, or just fire off in a random direction if there is no player to aim for
+        if frame_t - saucer.last_shot_fired > saucer.fire_rate {
+            let angle = match player_position {
+                Some(target) => {
+                    let to_target = target - drawable.position;
+                    to_target.y.atan2(to_target.x) + rng.gen_range(-aim_error..=aim_error)
+                }
+                None => rng.gen_range(0.0..TAU),
+            };
+
+            if let Some(bullet_texture_id) = texture_map.mapping.get("bullet") {
+                cmd.push(entities::enemy_bullet(drawable.position, angle, rules.bullet_wrap(), *bullet_texture_id, frame_t));
+            }
+            saucer.last_shot_fired = frame_t;
+        }
+    }
+}
+
+#[system]
+#[read_component(PowerUpComponent)]
+#[read_component(CollisionComponent)]
+#[read_component(PlayerComponent)]
+#[write_component(ActiveEffectsComponent)]
+#[write_component(ShieldComponent)]
+pub fn collect_power_ups(cmd: &mut CommandBuffer,
+                         world: &mut SubWorld,
+                         #[resource] lives_resource: &mut LivesResource,
+                         #[resource] time_resource: &TimeResource) {
+    let mut power_up_query = <(Entity, &PowerUpComponent, &CollisionComponent)>::query();
+    let power_ups: Vec<(Entity, PowerUpKind, Rect)> = power_up_query.iter(world)
+        .map(|(entity, power_up, collision)| (*entity, power_up.kind, collision.rect))
+        .collect();
+
+    let mut player_query = <(&PlayerComponent, &CollisionComponent, &mut ActiveEffectsComponent, Option<&mut ShieldComponent>)>::query();
+    for (_, player_collision, effects, mut shield) in player_query.iter_mut(world) {
+        for (power_up_entity, kind, power_up_rect) in power_ups.iter() {
+            if !player_collision.rect.overlaps(power_up_rect) {
+                continue;
+            }
+
+            // Timed effects are handed to the ship, anything else takes effect right away
+            cmd.remove(*power_up_entity);
+            if *kind == PowerUpKind::Shield {
+                if let Some(shield) = shield.as_deref_mut() {
+                    shield.points = shield.max_points;
+                }
+            }
+            match kind.duration() {
+                Some(duration) => effects.activate(*kind, time_resource.absolute_time + duration),
+                None => {
+                    if *kind == PowerUpKind::ExtraLife {
+                        lives_resource.lives += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[system(for_each)]
+pub fn expire_power_up_effects(effects: &mut ActiveEffectsComponent, #[resource] time_resource: &TimeResource) {
+    effects.effects.retain(|effect| effect.expires_at > time_resource.absolute_time);
+}
+
+#[system]
+#[write_component(WeaponComponent)]
+#[read_component(DrawableComponent)]
+#[read_component(ActiveEffectsComponent)]
+pub fn fire_weapons(cmd: &mut CommandBuffer, world: &mut SubWorld, #[resource] rules: &GameRules, #[resource] texture_map: &TextureMap, #[resource] time_resource: &TimeResource) {
+    let frame_t = time_resource.absolute_time;
+    let mut query = <(&mut WeaponComponent, &DrawableComponent, Option<&ActiveEffectsComponent>)>::query();
+    for (weapon, drawable, effects) in query.iter_mut(world) {
+        let rapid_fire = effects.is_some_and(|effects| effects.is_active(PowerUpKind::RapidFire));
+        let spread_shot = effects.is_some_and(|effects| effects.is_active(PowerUpKind::SpreadShot));
+        let cooldown = if rapid_fire {
+            weapon.cooldown * RAPID_FIRE_COOLDOWN_MULTIPLIER
+        } else {
+            weapon.cooldown
+        };
+
+        if weapon.trigger_pulled && frame_t - weapon.last_fired > cooldown {
+            weapon.last_fired = frame_t;
+            match weapon.pattern {
+                // Bursts are fired one shot at a time below, this just starts them off
+                ProjectilePattern::Burst{count, ..} => {
+                    weapon.burst_shots_remaining = count;
+                    weapon.next_burst_shot = frame_t;
+                }
+                _ => fire_volley(cmd, weapon, drawable, spread_shot, rules.bullet_wrap(), texture_map, frame_t),
+            }
+        }
+        weapon.trigger_pulled = false;
+
+        // Once a burst has started, it carries on firing whether or not the trigger is still held
+        if weapon.burst_shots_remaining > 0 && frame_t >= weapon.next_burst_shot {
+            fire_volley(cmd, weapon, drawable, spread_shot, rules.bullet_wrap(), texture_map, frame_t);
+            weapon.burst_shots_remaining -= 1;
+            if let ProjectilePattern::Burst{interval, ..} = weapon.pattern {
+                weapon.next_burst_shot = frame_t + interval;
+            }
+        }
+    }
+}
+
+/// Fires a single volley from the weapon, from wherever the drawable is and in the direction it is facing. Most
+/// patterns only fire one projectile per volley, but spreads (and the spread shot power up) add more either side
+fn fire_volley(cmd: &mut CommandBuffer, weapon: &WeaponComponent, drawable: &DrawableComponent, spread_shot: bool, wrap: WrapBehavior, texture_map: &TextureMap, frame_t: f64) {
+    let heading = drawable.rotation;
+    let mut angles: Vec<f32> = match weapon.pattern {
+        ProjectilePattern::Spread{count, angle} => {
+            let middle = count.saturating_sub(1) as f32 / 2.0;
+            (0..count).map(|shot| heading + (shot as f32 - middle) * angle).collect()
+        }
+        _ => vec![heading],
+    };
+    if spread_shot {
+        let first = angles.first().copied().unwrap_or(heading);
+        let last = angles.last().copied().unwrap_or(heading);
+        angles.insert(0, first - SPREAD_SHOT_ANGLE);
+        angles.push(last + SPREAD_SHOT_ANGLE);
+    }
+
+    let bullet_texture_id = texture_map.mapping.get("bullet");
+    for angle in angles {
+        match (weapon.pattern, bullet_texture_id) {
+            (ProjectilePattern::Laser{length}, _) => {
+                cmd.push(entities::laser_beam(drawable.position, angle, length, weapon, frame_t));
+            }
+            (ProjectilePattern::HomingMissile{turn_rate}, Some(texture_id)) => {
+                entities::spawn_homing_missile(cmd, drawable.position, angle, turn_rate, weapon, wrap, *texture_id, frame_t);
             }
+            (_, Some(texture_id)) => {
+                cmd.push(entities::bullet(drawable.position, angle, weapon, wrap, *texture_id, frame_t));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[system]
+#[read_component(HomingComponent)]
+#[read_component(AsteroidComponent)]
+#[read_component(SaucerComponent)]
+#[read_component(CollisionComponent)]
+#[write_component(VelocityComponent)]
+#[write_component(DrawableComponent)]
+pub fn steer_homing_missiles(world: &mut SubWorld) {
+    let mut target_query = <&CollisionComponent>::query()
+        .filter(component::<AsteroidComponent>() | component::<SaucerComponent>());
+    let targets: Vec<Vec2> = target_query.iter(world).map(|collision| collision.rect.center()).collect();
+
+    let mut missile_query = <(&HomingComponent, &mut VelocityComponent, &mut DrawableComponent)>::query();
+    for (homing, velocity, drawable) in missile_query.iter_mut(world) {
+        let position = drawable.position;
+        let nearest = targets.iter().min_by(|a, b| a.distance_squared(position).total_cmp(&b.distance_squared(position)));
+        if let Some(target) = nearest {
+            // Turn the shortest way around towards the target, but no faster than the missile is able to
+            let heading = velocity.velocity.y.atan2(velocity.velocity.x);
+            let to_target = *target - position;
+            let turn = (to_target.y.atan2(to_target.x) - heading + PI).rem_euclid(TAU) - PI;
+            let new_heading = heading + turn.clamp(-homing.turn_rate, homing.turn_rate);
+
+            velocity.velocity = Vec2::from_angle(new_heading) * velocity.velocity.length();
+            drawable.rotation = new_heading;
+        }
+    }
+}
+
+#[system(for_each)]
+pub fn recharge_shields(shield: &mut ShieldComponent, #[resource] time_resource: &TimeResource) {
+    if shield.points < shield.max_points && time_resource.absolute_time >= shield.recharge_at {
+        shield.points += 1;
+        shield.recharge_at = time_resource.absolute_time + shield.recharge_delay;
+    }
+}
+
+#[system]
+pub fn expire_damage_flashes(objects: &mut Query<(Entity, &DamageFlashComponent)>, cmd: &mut CommandBuffer, world: &mut SubWorld, #[resource] time_resource: &TimeResource) {
+    for (entity, flash) in objects.iter(world) {
+        if flash.started_at + DAMAGE_FLASH_DURATION < time_resource.absolute_time {
+            cmd.remove_component::<DamageFlashComponent>(*entity);
+        }
+    }
+}
+
+#[system]
+#[write_component(PlayerComponent)]
+#[write_component(DrawableComponent)]
+#[write_component(VelocityComponent)]
+#[read_component(CollisionComponent)]
+#[read_component(HyperspaceComponent)]
+#[allow(clippy::too_many_arguments)]
+pub fn hyperspace_jump(cmd: &mut CommandBuffer,
+                       world: &mut SubWorld,
+                       #[resource] lives_resource: &mut LivesResource,
+                       #[resource] respawn_resource: &mut RespawnResource,
+                       #[resource] game_over_resource: &mut GameOverResource,
+                       #[resource] texture_map: &TextureMap,
+                       #[resource] playfield: &PlayfieldDimensions,
+                       #[resource] time_resource: &TimeResource) {
+    let frame_t = time_resource.absolute_time;
+    let mut rng = rand::thread_rng();
+    let mut query = <(Entity, &mut PlayerComponent, &mut DrawableComponent, &mut VelocityComponent, &CollisionComponent)>::query()
+        .filter(!component::<HyperspaceComponent>());
+    for (entity, player, drawable, velocity, collision) in query.iter_mut(world) {
+        if !player.hyperspace_requested {
+            continue;
+        }
+        player.hyperspace_requested = false;
+        if frame_t - player.last_hyperspace_jump < player.hyperspace_cooldown {
+            continue;
+        }
+        player.last_hyperspace_jump = frame_t;
+
+        // Jumping through hyperspace is risky, every so often the ship doesn't make it out the other side
+        if rng.gen_bool(HYPERSPACE_MALFUNCTION_CHANCE) {
+            destroy_player(cmd, *entity, collision.rect.center(), lives_resource, respawn_resource, game_over_resource, texture_map, frame_t);
+            continue;
+        }
+
+        // Otherwise, the ship reappears somewhere random, at a standstill. It takes a moment to fully materialize,
+        // and until then it is helpless, even if it was invulnerable when it jumped
+        drawable.position = Vec2::new(rng.gen_range(0.0..=playfield.width), rng.gen_range(0.0..=playfield.height));
+        velocity.velocity = Vec2::ZERO;
+        cmd.remove_component::<InvulnerableComponent>(*entity);
+        cmd.add_component(*entity, HyperspaceComponent{started_at: frame_t, duration: HYPERSPACE_MATERIALIZE_DURATION});
+    }
+}
+
+#[system]
+pub fn finish_hyperspace(objects: &mut Query<(Entity, &HyperspaceComponent)>, cmd: &mut CommandBuffer, world: &mut SubWorld, #[resource] time_resource: &TimeResource) {
+    for (entity, hyperspace) in objects.iter(world) {
+        if hyperspace.started_at + hyperspace.duration < time_resource.absolute_time {
+            cmd.remove_component::<HyperspaceComponent>(*entity);
+        }
+    }
+}
+
+/// Shows the engine flame on any ship that is firing its engine this frame
+#[system(for_each)]
+pub fn animate_engines(emitter: &ParticleEmitterComponent, animation: &mut AnimationComponent, #[resource] time_resource: &TimeResource) {
+    let engine_animation = if emitter.active { &SHIP_THRUST } else { &SHIP_IDLE };
+    animation.play(engine_animation, time_resource.absolute_time);
+}
+
+/// Moves every animated drawable on to whichever frame of its animation should be showing now
+#[system(for_each)]
+pub fn animate_sprites(animation: &AnimationComponent, drawable: &mut DrawableComponent, #[resource] time_resource: &TimeResource) {
+    drawable.frame = animation.frame(time_resource.absolute_time);
+}
+
+#[system]
+#[read_component(ParticleBurstComponent)]
+#[read_component(DrawableComponent)]
+#[write_component(ParticleEmitterComponent)]
+pub fn emit_particles(cmd: &mut CommandBuffer,
+                      world: &mut SubWorld,
+                      #[resource] particle_pool: &mut ParticlePool,
+                      #[resource] time_resource: &TimeResource) {
+    let frame_t = time_resource.absolute_time;
+    let mut rng = rand::thread_rng();
+
+    let mut burst_query = <(Entity, &ParticleBurstComponent)>::query();
+    for (entity, burst) in burst_query.iter(world) {
+        particle_pool.emit(&mut rng, burst.effect, burst.position, burst.direction, frame_t);
+        cmd.remove(*entity);
+    }
+
+    // Emitters need to be switched on again every frame to keep going, like a ship's engine only fires while thrust
+    // is held down
+    let mut emitter_query = <(&mut ParticleEmitterComponent, &DrawableComponent)>::query();
+    for (emitter, drawable) in emitter_query.iter_mut(world) {
+        if !emitter.active {
+            continue;
+        }
+        emitter.active = false;
+
+        let heading = Vec2::from_angle(drawable.rotation);
+        let position = drawable.position - heading * emitter.trail_distance;
+        particle_pool.emit(&mut rng, emitter.effect, position, drawable.rotation + PI, frame_t);
+    }
+}
+
+#[system]
+pub fn update_particles(#[resource] particle_pool: &mut ParticlePool, #[resource] time_resource: &TimeResource) {
+    let frame_t = time_resource.absolute_time;
+    particle_pool.particles.retain(|particle| frame_t - particle.created_at < particle.lifetime);
+    for particle in particle_pool.particles.iter_mut() {
+        particle.position += particle.velocity;
+    }
+}
+
+#[system]
+pub fn apply_camera_kicks(objects: &mut Query<(Entity, &CameraKickComponent)>,
+                          cmd: &mut CommandBuffer,
+                          world: &mut SubWorld,
+                          #[resource] camera_effects: &mut CameraEffects) {
+    for (entity, kick) in objects.iter(world) {
+        camera_effects.add_trauma(kick.trauma);
+        camera_effects.hit_stop(kick.hit_stop);
+        cmd.remove(*entity);
+    }
+}
+
+#[system]
+pub fn decay_combo(#[resource] score_resource: &mut ScoreResource, #[resource] time_resource: &TimeResource) {
+    score_resource.decay_combo(time_resource.absolute_time);
+}
+
+#[system]
+pub fn award_milestones(cmd: &mut CommandBuffer,
+                        #[resource] score_resource: &ScoreResource,
+                        #[resource] lives_resource: &mut LivesResource,
+                        #[resource] milestones: &mut ScoreMilestones,
+                        #[resource] time_resource: &TimeResource) {
+    // A big enough combo can carry the score past more than one milestone at once, each of them is worth a life
+    while score_resource.score >= milestones.next_at {
+        lives_resource.lives += 1;
+        cmd.push(entities::milestone_reached(milestones.next_at, time_resource.absolute_time));
+        milestones.next_at += milestones.interval;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use legion::World;
+    use super::*;
+
+    #[test]
+    fn destroy_player_only_costs_one_life_per_frame() {
+        let mut world = World::default();
+        let player_entity = world.push((Vec2::ZERO,));
+        let mut cmd = CommandBuffer::new(&world);
+        let mut lives_resource = LivesResource{lives: 3};
+        let mut respawn_resource = RespawnResource{respawn_pending: false, died_at: f64::NEG_INFINITY};
+        let mut game_over_resource = GameOverResource{game_over: false};
+        let texture_map = TextureMap{mapping: HashMap::new()};
+
+        // Say an asteroid and a saucer's bullet both hit the ship in the same frame
+        for _ in 0..2 {
+            destroy_player(&mut cmd, player_entity, Vec2::ZERO, &mut lives_resource, &mut respawn_resource, &mut game_over_resource, &texture_map, 10.0);
         }
+        assert_eq!(lives_resource.lives, 2);
+
+        // A later ship can still be destroyed, of course
+        destroy_player(&mut cmd, player_entity, Vec2::ZERO, &mut lives_resource, &mut respawn_resource, &mut game_over_resource, &texture_map, 20.0);
+        assert_eq!(lives_resource.lives, 1);
     }
 }
@@ -0,0 +1,53 @@
+use crate::components::{ProjectilePattern, WeaponComponent};
+
+/// The default weapon, a single fast bullet straight ahead
+pub const BLASTER: WeaponComponent = weapon(0, "BLASTER", ProjectilePattern::Single, 15.0, 1.0, 1, 0.2);
+
+/// A wide fan of short lived bullets, good for clearing out anything close by
+pub const SCATTER_GUN: WeaponComponent = weapon(1, "SCATTER GUN", ProjectilePattern::Spread{count: 5, angle: 0.15}, 12.0, 0.6, 1, 0.5);
+
+/// A quick burst of bullets for each pull of the trigger
+pub const BURST_RIFLE: WeaponComponent = weapon(2, "BURST RIFLE", ProjectilePattern::Burst{count: 3, interval: 0.06}, 16.0, 0.9, 1, 0.6);
+
+/// A beam that cuts through everything in front of the ship for as long as the trigger is held
+pub const LASER: WeaponComponent = weapon(3, "LASER", ProjectilePattern::Laser{length: 300.0}, 0.0, 0.1, 1, 0.15);
+
+/// Slow missiles that steer themselves towards the nearest target
+pub const MISSILE_LAUNCHER: WeaponComponent = weapon(4, "MISSILES", ProjectilePattern::HomingMissile{turn_rate: 0.08}, 6.0, 2.5, 2, 0.6);
+
+/// Every weapon the player can switch between, in the order they are cycled through. Each weapon knows where it is in
+/// here, so keep their indexes in step with this order
+pub const ARSENAL: [WeaponComponent; 5] = [BLASTER, SCATTER_GUN, BURST_RIFLE, LASER, MISSILE_LAUNCHER];
+
+const fn weapon(arsenal_index: usize, name: &'static str, pattern: ProjectilePattern, speed: f32, lifetime: f64, damage: i32, cooldown: f64) -> WeaponComponent {
+    WeaponComponent{
+        arsenal_index,
+        name,
+        pattern,
+        speed,
+        lifetime,
+        damage,
+        cooldown,
+        last_fired: 0.0,
+        trigger_pulled: false,
+        burst_shots_remaining: 0,
+        next_burst_shot: 0.0,
+    }
+}
+
+/// Switches to the weapon that comes after the current one in the arsenal, wrapping back around to the first. Only the
+/// weapon itself changes, the state of the trigger carries over, so switching doesn't skip the cooldown. A burst that
+/// is still firing has to finish before the weapon can be switched, so it isn't cut short
+pub fn next_weapon(weapon: &mut WeaponComponent) {
+    if weapon.burst_shots_remaining > 0 {
+        return;
+    }
+
+    *weapon = WeaponComponent{
+        last_fired: weapon.last_fired,
+        trigger_pulled: weapon.trigger_pulled,
+        burst_shots_remaining: weapon.burst_shots_remaining,
+        next_burst_shot: weapon.next_burst_shot,
+        ..ARSENAL[(weapon.arsenal_index + 1) % ARSENAL.len()]
+    };
+}